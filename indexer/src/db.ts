@@ -34,12 +34,12 @@ function getDbPool() {
 // These assume the corresponding tables have been created in your Postgres database.
 
 // Table: certificates
-// Columns: asset_id (VARCHAR PK), creator (VARCHAR), licence_template_id (INTEGER), price (BIGINT), registration_timestamp (TIMESTAMPTZ), tx_signature (VARCHAR)
+// Columns: asset_id (VARCHAR PK), creator (VARCHAR), licence_template_id (INTEGER), price (BIGINT), payment_mint (VARCHAR), registration_timestamp (TIMESTAMPTZ), tx_signature (VARCHAR)
 async function insertCertificateRegistration(eventData: any, txSignature: string) {
     const pool = getDbPool();
     const query = `
-        INSERT INTO certificates (asset_id, creator, licence_template_id, price, registration_timestamp, tx_signature)
-        VALUES ($1, $2, $3, $4, NOW(), $5)
+        INSERT INTO certificates (asset_id, creator, licence_template_id, price, payment_mint, registration_timestamp, tx_signature)
+        VALUES ($1, $2, $3, $4, $5, NOW(), $6)
         ON CONFLICT (asset_id) DO NOTHING; // Ignore if already exists (idempotency)
     `;
     // Ensure data types match event structure from IDL
@@ -48,6 +48,7 @@ async function insertCertificateRegistration(eventData: any, txSignature: string
         eventData.creator.toBase58(),
         eventData.licenceTemplateId,
         eventData.price.toString(), // Store u64 as string/numeric in DB
+        eventData.paymentMint.toBase58(),
         txSignature
     ];
     try {
@@ -59,13 +60,38 @@ async function insertCertificateRegistration(eventData: any, txSignature: string
     }
 }
 
+// Follows two-step authority transfers so the `creator` column tracks the current owner.
+async function updateCertificateAuthority(eventData: any, txSignature: string) {
+    const pool = getDbPool();
+    const query = `
+        UPDATE certificates
+        SET creator = $1, tx_signature = $2
+        WHERE asset_id = $3;
+    `;
+    const values = [
+        eventData.newAuthority.toBase58(),
+        txSignature,
+        eventData.assetId.toBase58()
+    ];
+    try {
+        const res = await pool.query(query, values);
+        if (res.rowCount > 0) {
+            console.log(`[DB] Certificate Authority Updated: Asset ${values[2]}, Authority: ${values[0]}`);
+        } else {
+            console.warn(`[DB] Attempted to update authority for non-existent certificate: ${values[2]}`);
+        }
+    } catch (err) {
+        console.error("[DB] Error updating certificate authority:", err);
+    }
+}
+
 // Table: licences
-// Columns: licence_pda (VARCHAR PK), certificate_asset_id (VARCHAR FK), buyer (VARCHAR), purchase_price (BIGINT), purchase_timestamp (TIMESTAMPTZ), expiry_timestamp (TIMESTAMPTZ NULL), status (VARCHAR), last_update_timestamp (TIMESTAMPTZ), tx_signature (VARCHAR)
+// Columns: licence_pda (VARCHAR PK), certificate_asset_id (VARCHAR FK), buyer (VARCHAR), holder (VARCHAR), parent_licence (VARCHAR NULL), purchase_price (BIGINT), payment_mint (VARCHAR NULL), purchase_timestamp (TIMESTAMPTZ), expiry_timestamp (TIMESTAMPTZ NULL), status (VARCHAR), last_update_timestamp (TIMESTAMPTZ), tx_signature (VARCHAR)
 async function insertLicencePurchase(eventData: any, txSignature: string) {
     const pool = getDbPool();
     const query = `
-        INSERT INTO licences (licence_pda, certificate_asset_id, buyer, purchase_price, purchase_timestamp, status, last_update_timestamp, tx_signature)
-        VALUES ($1, $2, $3, $4, TO_TIMESTAMP($5), 'Active', NOW(), $6)
+        INSERT INTO licences (licence_pda, certificate_asset_id, buyer, holder, purchase_price, payment_mint, purchase_timestamp, expiry_timestamp, status, last_update_timestamp, tx_signature)
+        VALUES ($1, $2, $3, $3, $4, $5, TO_TIMESTAMP($6), TO_TIMESTAMP($7), 'Active', NOW(), $8)
         ON CONFLICT (licence_pda) DO NOTHING; // Ignore if already exists
     `;
     // Note: purchase_timestamp from Anchor event is i64 (seconds)
@@ -74,7 +100,9 @@ async function insertLicencePurchase(eventData: any, txSignature: string) {
         eventData.certificateDetails.toBase58(),
         eventData.buyer.toBase58(),
         eventData.purchasePrice.toString(),
+        eventData.paymentMint.toBase58(),
         eventData.purchaseTimestamp.toNumber(), // Convert BN/i64 to number for TO_TIMESTAMP
+        eventData.expiryTimestamp ? eventData.expiryTimestamp.toNumber() : null, // Perpetual licences have no expiry
         txSignature
     ];
      try {
@@ -85,6 +113,29 @@ async function insertLicencePurchase(eventData: any, txSignature: string) {
     }
 }
 
+async function insertSublicence(eventData: any, txSignature: string) {
+    const pool = getDbPool();
+    const query = `
+        INSERT INTO licences (licence_pda, certificate_asset_id, buyer, holder, parent_licence, purchase_price, purchase_timestamp, expiry_timestamp, status, last_update_timestamp, tx_signature)
+        VALUES ($1, $2, $3, $3, $4, 0, NOW(), TO_TIMESTAMP($5), 'Active', NOW(), $6)
+        ON CONFLICT (licence_pda) DO NOTHING;
+    `;
+    const values = [
+        eventData.sublicencePda.toBase58(),
+        eventData.certificateDetails.toBase58(),
+        eventData.sublicensee.toBase58(),
+        eventData.parentLicence.toBase58(),
+        eventData.expiryTimestamp ? eventData.expiryTimestamp.toNumber() : null, // Follows the parent's expiry
+        txSignature
+    ];
+     try {
+        await pool.query(query, values);
+        console.log(`[DB] Sublicence Inserted/Ignored: PDA ${values[0]} (parent ${values[3]})`);
+    } catch (err) {
+        console.error("[DB] Error inserting sublicence:", err);
+    }
+}
+
 async function updateLicenceStatus(licencePda: string, newStatus: 'Revoked' | 'Expired', txSignature: string) {
     const pool = getDbPool();
     const query = `
@@ -105,6 +156,46 @@ async function updateLicenceStatus(licencePda: string, newStatus: 'Revoked' | 'E
     }
 }
 
+async function updateLicenceRenewal(eventData: any, txSignature: string) {
+    const pool = getDbPool();
+    const query = `
+        UPDATE licences
+        SET status = 'Active', expiry_timestamp = TO_TIMESTAMP($1), last_update_timestamp = NOW(), tx_signature = $2
+        WHERE licence_pda = $3;
+    `;
+    const values = [eventData.expiryTimestamp.toNumber(), txSignature, eventData.licencePda.toBase58()];
+     try {
+        const res = await pool.query(query, values);
+        if (res.rowCount > 0) {
+            console.log(`[DB] Licence Renewed: PDA ${values[2]}, Expiry: ${values[0]}`);
+        } else {
+            console.warn(`[DB] Attempted to renew non-existent licence PDA: ${values[2]}`);
+        }
+    } catch (err) {
+        console.error("[DB] Error updating licence renewal:", err);
+    }
+}
+
+async function updateLicenceHolder(eventData: any, txSignature: string) {
+    const pool = getDbPool();
+    const query = `
+        UPDATE licences
+        SET holder = $1, last_update_timestamp = NOW(), tx_signature = $2
+        WHERE licence_pda = $3;
+    `;
+    const values = [eventData.newHolder.toBase58(), txSignature, eventData.licencePda.toBase58()];
+     try {
+        const res = await pool.query(query, values);
+        if (res.rowCount > 0) {
+            console.log(`[DB] Licence Transferred: PDA ${values[2]}, Holder: ${values[0]}`);
+        } else {
+            console.warn(`[DB] Attempted to transfer non-existent licence PDA: ${values[2]}`);
+        }
+    } catch (err) {
+        console.error("[DB] Error updating licence holder:", err);
+    }
+}
+
 // Table: royalty_splits (if needed separately, or store as JSON in certificates)
 // Columns:
 // - certificate_asset_id (VARCHAR, FOREIGN KEY references certificates.asset_id)
@@ -114,6 +205,10 @@ async function updateLicenceStatus(licencePda: string, newStatus: 'Revoked' | 'E
 
 module.exports = {
     insertCertificateRegistration,
+    updateCertificateAuthority,
     insertLicencePurchase,
     updateLicenceStatus,
+    updateLicenceRenewal,
+    updateLicenceHolder,
+    insertSublicence,
 };
\ No newline at end of file
@@ -2,7 +2,7 @@
 
 const { Connection, PublicKey, clusterApiUrl } = require("@solana/web3.js");
 const { initializeParsers, parseCertificateEvent, parseLicenceEvent } = require("./parser");
-const { insertCertificateRegistration, insertLicencePurchase, updateLicenceStatus } = require("./db");
+const { insertCertificateRegistration, updateCertificateAuthority, insertLicencePurchase, updateLicenceStatus, updateLicenceRenewal, updateLicenceHolder, insertSublicence } = require("./db");
 
 // --- Configuration ---
 // TODO: Replace with your actual deployed program IDs
@@ -43,6 +43,9 @@ async function main() {
                     if (event.name === "NewCertificateRegistered") {
                         insertCertificateRegistration(event.data, logsResult.signature).catch(console.error);
                     }
+                    else if (event.name === "AuthorityTransferred") {
+                        updateCertificateAuthority(event.data, logsResult.signature).catch(console.error);
+                    }
                     // Add handlers for other certificate events if created later
                 }
             });
@@ -76,6 +79,27 @@ async function main() {
                         }
 
                     }
+                    else if (event.name === "LicenceExpired") {
+                        if (event.data && event.data.licencePda) {
+                             updateLicenceStatus(event.data.licencePda.toBase58(), 'Expired', logsResult.signature).catch(console.error);
+                        } else {
+                            console.warn("Could not process LicenceExpired event: Missing data", event.data);
+                        }
+                    }
+                    else if (event.name === "LicenceRenewed") {
+                        updateLicenceRenewal(event.data, logsResult.signature).catch(console.error);
+                    }
+                    else if (event.name === "LicenceTransferred") {
+                        updateLicenceHolder(event.data, logsResult.signature).catch(console.error);
+                    }
+                    else if (event.name === "SublicenceIssued") {
+                        insertSublicence(event.data, logsResult.signature).catch(console.error);
+                    }
+                    else if (event.name === "SublicenceLapsed") {
+                        // Status is an Anchor enum object, e.g. { revoked: {} }
+                        const newStatus = event.data.status && event.data.status.revoked ? 'Revoked' : 'Expired';
+                        updateLicenceStatus(event.data.sublicencePda.toBase58(), newStatus, logsResult.signature).catch(console.error);
+                    }
                     // Add handlers for other licence events if created later
                 }
             });
@@ -0,0 +1,78 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::keccak;
+use anchor_lang::solana_program::program_option::COption;
+use spl_account_compression::program::SplAccountCompression;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Mint;
+
+use crate::state::*;
+
+/// Bubblegum leaf schema version byte for `LeafSchema::V1`.
+const LEAF_SCHEMA_V1: u8 = 1;
+
+/// Verifies that `asset` is an SPL Token or Token-2022 mint whose mint authority is `creator`.
+pub fn verify_mint_authority(asset: &AccountInfo, creator: &Pubkey) -> Result<()> {
+    require!(
+        *asset.owner == anchor_spl::token::ID || *asset.owner == spl_token_2022::ID,
+        CreatorClaimCertificateError::AssetNotMint
+    );
+
+    // Legacy mints share the base layout, so the Token-2022 unpacker handles both.
+    let data = asset.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)
+        .map_err(|_| error!(CreatorClaimCertificateError::AssetNotMint))?;
+    require!(
+        mint.base.mint_authority == COption::Some(*creator),
+        CreatorClaimCertificateError::NotMintAuthority
+    );
+
+    Ok(())
+}
+
+/// Derives the Bubblegum asset id of the leaf minted at `nonce` in `merkle_tree`.
+pub fn compressed_asset_id(merkle_tree: &Pubkey, nonce: u64) -> Pubkey {
+    Pubkey::find_program_address(
+        &[b"asset", merkle_tree.as_ref(), &nonce.to_le_bytes()],
+        &mpl_bubblegum::ID,
+    )
+    .0
+}
+
+/// Verifies that `asset_id` is a Bubblegum compressed asset owned by `owner`,
+/// by rebuilding its leaf hash and checking the proof against `merkle_tree`.
+/// Proof nodes are expected in `proof_accounts`, ordered from leaf to root.
+pub fn verify_compressed_leaf<'info>(
+    compression_program: &Program<'info, SplAccountCompression>,
+    merkle_tree: &UncheckedAccount<'info>,
+    proof_accounts: &[AccountInfo<'info>],
+    asset_id: &Pubkey,
+    owner: &Pubkey,
+    proof: &LeafProof,
+) -> Result<()> {
+    require_keys_eq!(
+        compressed_asset_id(&merkle_tree.key(), proof.nonce),
+        *asset_id,
+        CreatorClaimCertificateError::AssetIdMismatch
+    );
+
+    let leaf = keccak::hashv(&[
+        &[LEAF_SCHEMA_V1],
+        asset_id.as_ref(),
+        owner.as_ref(),
+        proof.delegate.as_ref(),
+        proof.nonce.to_le_bytes().as_ref(),
+        proof.data_hash.as_ref(),
+        proof.creator_hash.as_ref(),
+    ])
+    .to_bytes();
+
+    let cpi_ctx = CpiContext::new(
+        compression_program.to_account_info(),
+        spl_account_compression::cpi::accounts::VerifyLeaf {
+            merkle_tree: merkle_tree.to_account_info(),
+        },
+    )
+    .with_remaining_accounts(proof_accounts.to_vec());
+
+    spl_account_compression::cpi::verify_leaf(cpi_ctx, proof.root, leaf, proof.index)
+}
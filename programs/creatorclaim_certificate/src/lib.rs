@@ -1,9 +1,16 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use mpl_bubblegum::program::Bubblegum;
+use mpl_bubblegum::state::metaplex_adapter::MetadataArgs;
+use mpl_bubblegum::state::TreeConfig;
+use spl_account_compression::{program::SplAccountCompression, Noop};
 
-// Make state and errors available
-mod state;
+// Make state and errors available (the licence program reads CertificateDetails)
+pub mod state;
 use state::*;
 
+mod asset;
+
 declare_id!("CERTxxxxxxxxxxxxxxxxxx"); // Replace with actual Program ID after deploy
 
 // Define the program ID for use in constraints
@@ -30,8 +37,10 @@ pub mod creatorclaim_certificate {
     ///     ctx: Context containing accounts needed for the instruction.
     ///     metadata_uri_hash: The SHA-256 hash of the off-chain metadata URI.
     ///     licence_template_id: The ID of the licence template.
-    ///     price: The price required to purchase a licence (e.g., in USDC cents).
+    ///     price: The price required to purchase a licence, in base units of `payment_mint`.
+    ///     payment_mint: The mint the price is denominated in (e.g., USDC).
     ///     royalty_splits: The vector defining royalty distribution.
+    ///     asset_verification: Opt-in proof that the creator controls `asset_id_or_mint_pk`.
     ///
     /// Accounts:
     ///     creator: The signer creating the certificate details, pays for account rent.
@@ -39,55 +48,702 @@ pub mod creatorclaim_certificate {
     ///         Seeds: ["certificate_details", asset_id_or_mint_pk.key().as_ref()]
     ///         Payer: creator
     ///         Space: CertificateDetails::LEN
+    ///     asset_registration: The asset's permanent registration marker, to be initialized.
+    ///         Seeds: ["asset_registration", asset_id_or_mint_pk.key().as_ref()]
+    ///         Already existing if the asset was registered before, even if since retired.
     ///     asset_id_or_mint_pk: An account representing the unique asset (e.g., the cNFT mint pubkey).
     ///                            Used as a seed for the PDA. Could be any unique identifier account.
     ///     system_program: Required by Anchor for creating accounts.
-    pub fn register_certificate(
-        ctx: Context<RegisterCertificate>,
+    ///     merkle_tree: (Optional) The Bubblegum tree holding the asset, for `CompressedLeaf`.
+    ///     compression_program: (Optional) SPL Account Compression, for `CompressedLeaf`.
+    ///     remaining_accounts: Merkle proof nodes (leaf to root), for `CompressedLeaf`.
+    pub fn register_certificate<'info>(
+        ctx: Context<'_, '_, '_, 'info, RegisterCertificate<'info>>,
         metadata_uri_hash: [u8; 32],
         licence_template_id: u16,
         price: u64,
+        payment_mint: Pubkey,
         royalty_splits: Vec<RoyaltySplit>,
+        asset_verification: AssetVerification,
     ) -> Result<()> {
 
-        // Validate inputs
+        // Optionally prove the creator controls the asset, so nobody can squat on it
+        let asset_id = ctx.accounts.asset_id_or_mint_pk.key();
+        match &asset_verification {
+            AssetVerification::Unverified => {}
+            AssetVerification::Mint => {
+                asset::verify_mint_authority(
+                    &ctx.accounts.asset_id_or_mint_pk,
+                    &ctx.accounts.creator.key(),
+                )?;
+            }
+            AssetVerification::CompressedLeaf(proof) => {
+                let (Some(merkle_tree), Some(compression_program)) = (
+                    ctx.accounts.merkle_tree.as_ref(),
+                    ctx.accounts.compression_program.as_ref(),
+                ) else {
+                    return err!(CreatorClaimCertificateError::MissingCompressionAccounts);
+                };
+                asset::verify_compressed_leaf(
+                    compression_program,
+                    merkle_tree,
+                    ctx.remaining_accounts,
+                    &asset_id,
+                    &ctx.accounts.creator.key(),
+                    proof,
+                )?;
+            }
+        }
+
+        // Validate inputs and populate the account data
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        certificate_details.initialize(
+            ctx.accounts.creator.key(),
+            metadata_uri_hash,
+            licence_template_id,
+            price,
+            payment_mint,
+            royalty_splits,
+        )?;
+        certificate_details.asset_verified = asset_verification != AssetVerification::Unverified;
+        certificate_details.bump = ctx.bumps.certificate_details; // Anchor automatically gets bump
+        ctx.accounts.asset_registration.bump = ctx.bumps.asset_registration;
+
+        msg!("Certificate details registered for asset: {}", ctx.accounts.asset_id_or_mint_pk.key());
+        emit!(NewCertificateRegistered {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            creator: ctx.accounts.creator.key(),
+            licence_template_id,
+            price,
+            payment_mint,
+            asset_verified: certificate_details.asset_verified,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to mint the certificate as a compressed NFT and register its details
+    /// in a single transaction. The cNFT is minted to the creator through Bubblegum, and
+    /// the CertificateDetails PDA is derived from the asset id Bubblegum assigns to it.
+    /// The metadata hash is the SHA-256 of `metadata.uri`. An Unverified registration
+    /// made for the asset id before the mint is taken over, so nobody can block the id.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     licence_template_id: The ID of the licence template.
+    ///     price: The price required to purchase a licence, in base units of `payment_mint`.
+    ///     payment_mint: The mint the price is denominated in (e.g., USDC).
+    ///     royalty_splits: The vector defining royalty distribution.
+    ///     metadata: The Bubblegum metadata of the cNFT to mint.
+    ///
+    /// Accounts:
+    ///     creator: The signer minting the certificate; pays rent and receives the cNFT.
+    ///              Must be the tree delegate unless the tree is public.
+    ///     certificate_details: The PDA account to be initialized, or an Unverified one to take over.
+    ///         Seeds: ["certificate_details", asset_id_or_mint_pk.key().as_ref()]
+    ///     asset_registration: The asset's permanent registration marker, created if missing.
+    ///         Seeds: ["asset_registration", asset_id_or_mint_pk.key().as_ref()]
+    ///     asset_id_or_mint_pk: The asset id the new leaf will receive
+    ///                          (["asset", merkle_tree, num_minted] under Bubblegum).
+    ///     tree_authority: The Bubblegum TreeConfig of `merkle_tree`.
+    ///     merkle_tree: The concurrent Merkle tree to mint into.
+    ///     bubblegum_program, log_wrapper, compression_program, system_program: Programs used by the mint.
+    pub fn mint_certificate(
+        ctx: Context<MintCertificate>,
+        licence_template_id: u16,
+        price: u64,
+        payment_mint: Pubkey,
+        royalty_splits: Vec<RoyaltySplit>,
+        metadata: MetadataArgs,
+    ) -> Result<()> {
+        // --- Capacity & Asset Id Check ---
+        let tree_config = &ctx.accounts.tree_authority;
         require!(
-            metadata_uri_hash != [0u8; 32],
-            CreatorClaimCertificateError::MissingMetadataHash
+            tree_config.num_minted < tree_config.total_mint_capacity,
+            CreatorClaimCertificateError::TreeCapacity
+        );
+        // Bubblegum uses the pre-mint counter as the new leaf's nonce.
+        require_keys_eq!(
+            asset::compressed_asset_id(&ctx.accounts.merkle_tree.key(), tree_config.num_minted),
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            CreatorClaimCertificateError::AssetIdMismatch
         );
-        require!(price > 0, CreatorClaimCertificateError::ZeroPriceNotAllowed);
-        CertificateDetails::validate_splits(&royalty_splits)?;
 
-        // Get the certificate_details account from the context
+        let metadata_uri_hash = hash(metadata.uri.as_bytes()).to_bytes();
+
+        // --- Take Over Squatted Registrations ---
+        // Asset ids are predictable, so anyone may have registered this one Unverified
+        // before it was minted. Minting proves ownership, so the verified certificate
+        // replaces that listing. Licences sold under it stay counted; the new
+        // authority can revoke them.
         let certificate_details = &mut ctx.accounts.certificate_details;
+        let taken_over = certificate_details.authority != Pubkey::default();
+        if taken_over {
+            // A takedown must not be escaped by minting over the listing
+            require!(
+                certificate_details.status != CertificateStatus::Suspended,
+                CreatorClaimCertificateError::CertificateSuspended
+            );
+        }
+        let active_licences = certificate_details.active_licences;
+        let exclusive_licence = certificate_details.exclusive_licence;
+        let exclusive_until = certificate_details.exclusive_until;
 
-        // Populate the account data
-        certificate_details.authority = ctx.accounts.creator.key();
-        certificate_details.metadata_uri_hash = metadata_uri_hash;
-        certificate_details.licence_template_id = licence_template_id;
-        certificate_details.price = price;
-        certificate_details.royalty_splits = royalty_splits;
-        certificate_details.bump = ctx.bumps.certificate_details; // Anchor automatically gets bump
+        // --- Populate Certificate ---
+        certificate_details.initialize(
+            ctx.accounts.creator.key(),
+            metadata_uri_hash,
+            licence_template_id,
+            price,
+            payment_mint,
+            royalty_splits,
+        )?;
+        certificate_details.active_licences = active_licences;
+        certificate_details.exclusive_licence = exclusive_licence;
+        certificate_details.exclusive_until = exclusive_until;
+        certificate_details.asset_verified = true;
+        certificate_details.bump = ctx.bumps.certificate_details;
+        ctx.accounts.asset_registration.bump = ctx.bumps.asset_registration;
 
-        msg!("Certificate details registered for asset: {}", ctx.accounts.asset_id_or_mint_pk.key());
+        // --- Mint cNFT ---
+        let creator = ctx.accounts.creator.to_account_info();
+        mpl_bubblegum::cpi::mint_v1(
+            CpiContext::new(
+                ctx.accounts.bubblegum_program.to_account_info(),
+                mpl_bubblegum::cpi::accounts::MintV1 {
+                    tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                    leaf_owner: creator.clone(),
+                    leaf_delegate: creator.clone(),
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    payer: creator.clone(),
+                    tree_delegate: creator,
+                    log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                    compression_program: ctx.accounts.compression_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                },
+            ),
+            metadata,
+        )?;
+
+        msg!(
+            "Certificate cNFT minted and registered for asset: {} (took over unverified listing: {})",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            taken_over
+        );
         emit!(NewCertificateRegistered {
             asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
             creator: ctx.accounts.creator.key(),
             licence_template_id,
             price,
+            payment_mint,
+            asset_verified: true,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to nominate a new authority for a certificate (step 1 of 2).
+    /// The transfer only takes effect once the nominee calls `accept_authority`,
+    /// so a typo in the new key cannot lock the certificate.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     new_authority: The key to nominate. None cancels a pending transfer.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer).
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn propose_authority(
+        ctx: Context<UpdateCertificate>,
+        new_authority: Option<Pubkey>,
+    ) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        certificate_details.pending_authority = new_authority;
+
+        msg!(
+            "Pending authority for asset {} set to {:?}",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            new_authority
+        );
+        Ok(())
+    }
+
+    /// Instruction to accept a pending authority transfer (step 2 of 2).
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///
+    /// Accounts:
+    ///     new_authority: The nominated key (signer). Must match `pending_authority`.
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let previous_authority = certificate_details.authority;
+
+        certificate_details.authority = ctx.accounts.new_authority.key();
+        certificate_details.pending_authority = None;
+
+        msg!(
+            "Authority for asset {} transferred from {} to {}",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            previous_authority,
+            certificate_details.authority
+        );
+        emit!(AuthorityTransferred {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            previous_authority,
+            new_authority: certificate_details.authority,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to replace the metadata hash of a certificate, e.g. after fixing
+    /// the off-chain metadata or re-pinning it to a new Arweave transaction.
+    /// Bumps `metadata_version` and emits the old and new hash so every revision
+    /// remains provable from the transaction history.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     new_metadata_uri_hash: The SHA-256 hash of the updated metadata URI.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer).
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn update_metadata_hash(
+        ctx: Context<UpdateCertificate>,
+        new_metadata_uri_hash: [u8; 32],
+    ) -> Result<()> {
+        require!(
+            new_metadata_uri_hash != [0u8; 32],
+            CreatorClaimCertificateError::MissingMetadataHash
+        );
+
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let old_metadata_uri_hash = certificate_details.metadata_uri_hash;
+        require!(
+            new_metadata_uri_hash != old_metadata_uri_hash,
+            CreatorClaimCertificateError::MetadataHashUnchanged
+        );
+
+        certificate_details.metadata_uri_hash = new_metadata_uri_hash;
+        certificate_details.metadata_version = certificate_details
+            .metadata_version
+            .checked_add(1)
+            .ok_or(CreatorClaimCertificateError::MetadataVersionOverflow)?;
+
+        msg!(
+            "Metadata hash for asset {} updated to version {}",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details.metadata_version
+        );
+        emit!(MetadataHashUpdated {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            old_metadata_uri_hash,
+            new_metadata_uri_hash,
+            metadata_version: certificate_details.metadata_version,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to change the listing terms (price, payment mint and licence template) of a certificate.
+    /// Existing `Licence` accounts keep the `purchase_price` they paid, so repricing never
+    /// touches them. Switching template is rejected while licences are still active,
+    /// because those licences were granted under the current template's terms.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     price: The new price required to purchase a licence.
+    ///     payment_mint: The mint the new price is denominated in.
+    ///     licence_template_id: The new licence template ID.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer).
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn update_listing(
+        ctx: Context<UpdateCertificate>,
+        price: u64,
+        payment_mint: Pubkey,
+        licence_template_id: u16,
+    ) -> Result<()> {
+        require!(price > 0, CreatorClaimCertificateError::ZeroPriceNotAllowed);
+        require!(payment_mint != Pubkey::default(), CreatorClaimCertificateError::MissingPaymentMint);
+
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let old_price = certificate_details.price;
+        let old_payment_mint = certificate_details.payment_mint;
+        let old_licence_template_id = certificate_details.licence_template_id;
+
+        if licence_template_id != old_licence_template_id {
+            require!(
+                certificate_details.active_licences == 0,
+                CreatorClaimCertificateError::LicencesOutstanding
+            );
+        }
+
+        certificate_details.price = price;
+        certificate_details.payment_mint = payment_mint;
+        certificate_details.licence_template_id = licence_template_id;
+
+        msg!(
+            "Listing for asset {} updated: price {} -> {}, mint {} -> {}, template {} -> {}",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            old_price,
+            price,
+            old_payment_mint,
+            payment_mint,
+            old_licence_template_id,
+            licence_template_id
+        );
+        emit!(ListingUpdated {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            old_price,
+            new_price: price,
+            old_payment_mint,
+            new_payment_mint: payment_mint,
+            old_licence_template_id,
+            new_licence_template_id: licence_template_id,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to accept (or stop accepting) native SOL for licences of a certificate.
+    /// The SOL price sits alongside the token listing; buyers choose which one to pay.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     lamport_price: The price in lamports. None stops accepting SOL.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer).
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn set_lamport_price(ctx: Context<UpdateCertificate>, lamport_price: Option<u64>) -> Result<()> {
+        if let Some(lamports) = lamport_price {
+            require!(lamports > 0, CreatorClaimCertificateError::ZeroPriceNotAllowed);
+        }
+
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let old_lamport_price = certificate_details.lamport_price;
+        certificate_details.lamport_price = lamport_price;
+
+        msg!(
+            "SOL price for asset {} updated: {:?} -> {:?}",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            old_lamport_price,
+            lamport_price
+        );
+        emit!(LamportPriceUpdated {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            old_lamport_price,
+            new_lamport_price: lamport_price,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to replace the royalty split table of a certificate.
+    /// The new table must pass the same validation as at registration, and every
+    /// beneficiary whose share decreases (including removal) must consent by signing.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     new_royalty_splits: The replacement royalty distribution.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer).
+    ///     certificate_details: The CertificateDetails PDA to update.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    ///     remaining_accounts: Signer accounts of every beneficiary whose share decreases
+    ///                         (the authority's own signature already counts for itself).
+    pub fn amend_royalty_splits(
+        ctx: Context<UpdateCertificate>,
+        new_royalty_splits: Vec<RoyaltySplit>,
+    ) -> Result<()> {
+        CertificateDetails::validate_splits(&new_royalty_splits)?;
+
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let previous_royalty_splits = certificate_details.royalty_splits.clone();
+
+        // --- Consent Check ---
+        for split in previous_royalty_splits.iter() {
+            let new_share = CertificateDetails::share_of(&new_royalty_splits, &split.beneficiary);
+            if new_share < split.share_bps {
+                let has_signed = split.beneficiary == ctx.accounts.authority.key()
+                    || ctx
+                        .remaining_accounts
+                        .iter()
+                        .any(|account| account.key() == split.beneficiary && account.is_signer);
+                require!(has_signed, CreatorClaimCertificateError::MissingBeneficiaryConsent);
+            }
+        }
+
+        certificate_details.royalty_splits = new_royalty_splits.clone();
+
+        msg!(
+            "Royalty splits for asset {} amended ({} -> {} recipients)",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            previous_royalty_splits.len(),
+            new_royalty_splits.len()
+        );
+        emit!(RoyaltySplitsAmended {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            previous_royalty_splits,
+            new_royalty_splits,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to delist a certificate. The certificate is marked Retired so
+    /// `purchase_licence` refuses new sales. If no active licences remain, the
+    /// account is closed and its rent refunded to the authority; otherwise the
+    /// authority can call this again once the outstanding licences have ended.
+    /// The asset's `AssetRegistration` marker stays behind, so the asset cannot be
+    /// registered again once the account is closed.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///
+    /// Accounts:
+    ///     authority: The current authority of the certificate (signer, receives rent).
+    ///     certificate_details: The CertificateDetails PDA to retire.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn retire_certificate(ctx: Context<RetireCertificate>) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        // A takedown must not be escaped by closing and re-registering the asset
+        require!(
+            certificate_details.status != CertificateStatus::Suspended,
+            CreatorClaimCertificateError::CertificateSuspended
+        );
+        certificate_details.status = CertificateStatus::Retired;
+
+        let closed = certificate_details.active_licences == 0;
+        if closed {
+            certificate_details.close(ctx.accounts.authority.to_account_info())?;
+        }
+
+        msg!(
+            "Certificate for asset {} retired (account closed: {})",
+            ctx.accounts.asset_id_or_mint_pk.key(),
+            closed
+        );
+        emit!(CertificateRetired {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            authority: ctx.accounts.authority.key(),
+            closed,
+        });
+
+        Ok(())
+    }
+
+    /// Instruction to create the PlatformConfig PDA. Only the program's upgrade
+    /// authority can call it, so the admin set cannot be front-run at deployment.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     params: Admin set, treasury, fee, accepted payment mints, licence program and escrow period.
+    ///
+    /// Accounts:
+    ///     upgrade_authority: The program's upgrade authority (signer, pays rent).
+    ///     platform_config: The PDA to be initialized. Seeds: ["platform_config"]
+    ///     program: This program, used to locate its ProgramData account.
+    ///     program_data: The ProgramData account holding the upgrade authority.
+    ///     system_program: Required by Anchor for creating accounts.
+    pub fn initialize_config(ctx: Context<InitializeConfig>, params: ConfigParams) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+        platform_config.apply(params)?;
+        platform_config.bump = ctx.bumps.platform_config;
+
+        msg!("Platform config initialized with {} admin(s)", platform_config.admins.len());
+        emit!(ConfigUpdated {
+            admins: platform_config.admins.clone(),
+            treasury: platform_config.treasury,
+            fee_bps: platform_config.fee_bps,
+            accepted_payment_mints: platform_config.accepted_payment_mints.clone(),
+            licence_program: platform_config.licence_program,
+            escrow_period: platform_config.escrow_period,
+        });
+        Ok(())
+    }
+
+    /// Instruction for a current admin to replace the platform config, e.g. to
+    /// rotate admins or change the fee without redeploying.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///     params: The new admin set, treasury, fee, accepted payment mints, licence program and escrow period.
+    ///
+    /// Accounts:
+    ///     admin: One of the current admins (signer).
+    ///     platform_config: The PlatformConfig PDA.
+    pub fn update_config(ctx: Context<UpdateConfig>, params: ConfigParams) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+        platform_config.apply(params)?;
+
+        msg!("Platform config updated by admin: {}", ctx.accounts.admin.key());
+        emit!(ConfigUpdated {
+            admins: platform_config.admins.clone(),
+            treasury: platform_config.treasury,
+            fee_bps: platform_config.fee_bps,
+            accepted_payment_mints: platform_config.accepted_payment_mints.clone(),
+            licence_program: platform_config.licence_program,
+            escrow_period: platform_config.escrow_period,
+        });
+        Ok(())
+    }
+
+    /// Instruction for the platform admin to suspend a certificate, e.g. on a
+    /// validated DMCA notice. Suspended certificates cannot sell licences.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///
+    /// Accounts:
+    ///     admin: One of the admins stored in `platform_config` (signer).
+    ///     platform_config: The PlatformConfig PDA.
+    ///     certificate_details: The CertificateDetails PDA to suspend.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn suspend_certificate(ctx: Context<AdminUpdateCertificate>) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        require!(
+            certificate_details.status == CertificateStatus::Active,
+            CreatorClaimCertificateError::CertificateNotActive
+        );
+        certificate_details.status = CertificateStatus::Suspended;
+
+        msg!("Certificate for asset {} suspended", ctx.accounts.asset_id_or_mint_pk.key());
+        emit!(CertificateSuspended {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            admin: ctx.accounts.admin.key(),
         });
 
         Ok(())
     }
 
-    // Potentially add other instructions later:
-    // - update_authority(...)
-    // - update_metadata_hash(...)
+    /// Instruction for the platform admin to lift a suspension.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed for the instruction.
+    ///
+    /// Accounts:
+    ///     admin: One of the admins stored in `platform_config` (signer).
+    ///     platform_config: The PlatformConfig PDA.
+    ///     certificate_details: The CertificateDetails PDA to reinstate.
+    ///     asset_id_or_mint_pk: The asset key the PDA is derived from.
+    pub fn reinstate_certificate(ctx: Context<AdminUpdateCertificate>) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        require!(
+            certificate_details.status == CertificateStatus::Suspended,
+            CreatorClaimCertificateError::CertificateNotSuspended
+        );
+        certificate_details.status = CertificateStatus::Active;
+
+        msg!("Certificate for asset {} reinstated", ctx.accounts.asset_id_or_mint_pk.key());
+        emit!(CertificateReinstated {
+            asset_id: ctx.accounts.asset_id_or_mint_pk.key(),
+            certificate_details: certificate_details.key(),
+            admin: ctx.accounts.admin.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Hook called by the licence program (via CPI) when a licence becomes Active.
+    /// Rejects the activation while another licence holds an exclusive lock.
+    ///
+    /// Args:
+    ///     licence: The Licence PDA being activated.
+    ///
+    /// Accounts:
+    ///     licence_authority: The licence program's `licence_authority` PDA (signer).
+    ///     platform_config: The PlatformConfig PDA naming the licence program.
+    ///     certificate_details: The CertificateDetails account the licence belongs to.
+    pub fn record_licence_activated(ctx: Context<LicenceHook>, licence: Pubkey) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(exclusive_licence) = certificate_details.active_exclusive_licence(now) {
+            require_keys_eq!(
+                exclusive_licence,
+                licence,
+                CreatorClaimCertificateError::ExclusiveLicenceActive
+            );
+        }
+
+        certificate_details.active_licences = certificate_details
+            .active_licences
+            .checked_add(1)
+            .ok_or(CreatorClaimCertificateError::LicenceCountOverflow)?;
+        Ok(())
+    }
+
+    /// Hook called by the licence program (via CPI) when an Active licence is revoked or expires.
+    /// Releases the exclusive lock if `licence` held it.
+    ///
+    /// Args:
+    ///     licence: The Licence PDA being released.
+    ///
+    /// Accounts:
+    ///     licence_authority: The licence program's `licence_authority` PDA (signer).
+    ///     platform_config: The PlatformConfig PDA naming the licence program.
+    ///     certificate_details: The CertificateDetails account the licence belongs to.
+    pub fn record_licence_released(ctx: Context<LicenceHook>, licence: Pubkey) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        certificate_details.active_licences = certificate_details
+            .active_licences
+            .checked_sub(1)
+            .ok_or(CreatorClaimCertificateError::LicenceCountOverflow)?;
+
+        if certificate_details.exclusive_licence == Some(licence) {
+            certificate_details.exclusive_licence = None;
+            certificate_details.exclusive_until = None;
+        }
+        Ok(())
+    }
+
+    /// Hook called by the licence program (via CPI) to grant or extend an exclusive lock.
+    /// A new lock needs the work to have no other Active licences. The current holder
+    /// may extend a live lock (e.g. on renewal); once its lock has lapsed it may only
+    /// retake it while its own licence is the only one counted.
+    ///
+    /// Args:
+    ///     licence: The Licence PDA taking exclusive rights.
+    ///     exclusive_until: When the lock lapses. None for a perpetual exclusive licence.
+    ///
+    /// Accounts:
+    ///     licence_authority: The licence program's `licence_authority` PDA (signer).
+    ///     platform_config: The PlatformConfig PDA naming the licence program.
+    ///     certificate_details: The CertificateDetails account the licence belongs to.
+    pub fn record_exclusive_licence(
+        ctx: Context<LicenceHook>,
+        licence: Pubkey,
+        exclusive_until: Option<i64>,
+    ) -> Result<()> {
+        let certificate_details = &mut ctx.accounts.certificate_details;
+        certificate_details.check_exclusive_lock(&licence, Clock::get()?.unix_timestamp)?;
+
+        certificate_details.exclusive_licence = Some(licence);
+        certificate_details.exclusive_until = exclusive_until;
+        Ok(())
+    }
 }
 
 /// Context for the `register_certificate` instruction.
 #[derive(Accounts)]
-#[instruction(metadata_uri_hash: [u8; 32], licence_template_id: u16, price: u64, royalty_splits: Vec<RoyaltySplit>)]
+#[instruction(metadata_uri_hash: [u8; 32], licence_template_id: u16, price: u64, payment_mint: Pubkey, royalty_splits: Vec<RoyaltySplit>, asset_verification: AssetVerification)]
 pub struct RegisterCertificate<'info> {
     #[account(mut)]
     pub creator: Signer<'info>,
@@ -95,32 +751,226 @@ pub struct RegisterCertificate<'info> {
     /// Initialize the PDA account for certificate details.
     /// Seeds constraint ensures PDA is derived correctly.
     /// Space constraint ensures enough space is allocated.
-    /// Owner constraint ensures the account belongs to this program.
     #[account(
         init,
         payer = creator,
         space = CertificateDetails::LEN,
         seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
-        bump,
-        owner = system_program.key() // Initial owner is system program for `init`
-        // After init, the owner will be this program's ID. Anchor handles the final owner check implicitly.
+        bump
     )]
     pub certificate_details: Account<'info, CertificateDetails>,
 
-    /// CHECK: This account solely provides a unique key for the PDA seed.
+    /// Outlives a retired certificate, so `init` fails for an asset registered before.
+    #[account(
+        init,
+        payer = creator,
+        space = AssetRegistration::LEN,
+        seeds = [b"asset_registration", asset_id_or_mint_pk.key().as_ref()],
+        bump
+    )]
+    pub asset_registration: Account<'info, AssetRegistration>,
+
+    /// CHECK: This account provides the unique key for the PDA seed.
     /// The key of this account is used as a seed for the `certificate_details` PDA.
-    /// It MUST be a unique identifier for the creative work (e.g., the cNFT mint address).
-    /// No data is read or written from this account within this instruction,
-    /// and no ownership or type checks are performed here.
-    /// Assumption: The caller provides the correct, unique public key representing the asset.
-    /// Risk: If a non-unique key is provided, it could lead to PDA collisions or incorrect association.
-    /// Future Enhancement: If needed, add constraints here to verify it's a valid Mint address
-    /// owned by the appropriate token/NFT program, but this increases complexity.
+    /// It MUST be a unique identifier for the creative work (e.g., the cNFT asset id or mint address).
+    /// With `AssetVerification::Unverified` no data is read and no ownership or type checks
+    /// are performed; `Mint` and `CompressedLeaf` verify it in the instruction (see `asset.rs`).
+    /// Risk: An unverified key can be registered by anyone, so clients should prefer verified certificates.
+    pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Validated by the compression program when verifying a `CompressedLeaf`.
+    pub merkle_tree: Option<UncheckedAccount<'info>>,
+    pub compression_program: Option<Program<'info, SplAccountCompression>>,
+}
+
+/// Context for the `mint_certificate` instruction.
+#[derive(Accounts)]
+pub struct MintCertificate<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    /// May already hold an Unverified registration of the asset id, which is taken over.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = CertificateDetails::LEN,
+        seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
+        bump
+    )]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// Already exists if the asset id was registered Unverified before the mint.
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = AssetRegistration::LEN,
+        seeds = [b"asset_registration", asset_id_or_mint_pk.key().as_ref()],
+        bump
+    )]
+    pub asset_registration: Account<'info, AssetRegistration>,
+
+    /// CHECK: Must equal the asset id Bubblegum assigns to the leaf being minted;
+    /// checked in the instruction against `merkle_tree` and `tree_authority.num_minted`.
+    pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [merkle_tree.key().as_ref()],
+        bump,
+        seeds::program = bubblegum_program.key()
+    )]
+    pub tree_authority: Account<'info, TreeConfig>,
+
+    /// CHECK: Validated by Bubblegum and the compression program during the mint.
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    pub bubblegum_program: Program<'info, Bubblegum>,
+    pub log_wrapper: Program<'info, Noop>,
+    pub compression_program: Program<'info, SplAccountCompression>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for authority-gated updates to an existing certificate.
+#[derive(Accounts)]
+pub struct UpdateCertificate<'info> {
+    /// The current authority stored in `certificate_details`.
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
+        bump = certificate_details.bump,
+        has_one = authority @ CreatorClaimCertificateError::NotAuthorised
+    )]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// CHECK: Only used as the seed of `certificate_details`; the seeds constraint
+    /// ties the two together so events can report the asset id.
+    pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+}
+
+/// Context for the `retire_certificate` instruction.
+#[derive(Accounts)]
+pub struct RetireCertificate<'info> {
+    /// The current authority; receives the rent if the account is closed.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
+        bump = certificate_details.bump,
+        has_one = authority @ CreatorClaimCertificateError::NotAuthorised
+    )]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// CHECK: Only used as the seed of `certificate_details`.
     pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+}
+
+/// Context for the `initialize_config` instruction.
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = PlatformConfig::LEN,
+        seeds = [b"platform_config"],
+        bump
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, crate::program::CreatorclaimCertificate>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ CreatorClaimCertificateError::NotAuthorised
+    )]
+    pub program_data: Account<'info, ProgramData>,
 
     pub system_program: Program<'info, System>,
 }
 
+/// Context for the `update_config` instruction.
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimCertificateError::NotAuthorised
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Context for admin-gated updates to a certificate (suspension / reinstatement).
+#[derive(Accounts)]
+pub struct AdminUpdateCertificate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimCertificateError::NotAuthorised
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
+        bump = certificate_details.bump
+    )]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// CHECK: Only used as the seed of `certificate_details`.
+    pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+}
+
+/// Context for the `accept_authority` instruction.
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// The key nominated through `propose_authority`.
+    pub new_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"certificate_details", asset_id_or_mint_pk.key().as_ref()],
+        bump = certificate_details.bump,
+        constraint = certificate_details.pending_authority.is_some() @ CreatorClaimCertificateError::NoPendingAuthority,
+        constraint = certificate_details.pending_authority == Some(new_authority.key()) @ CreatorClaimCertificateError::PendingAuthorityMismatch
+    )]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// CHECK: Only used as the seed of `certificate_details`.
+    pub asset_id_or_mint_pk: UncheckedAccount<'info>,
+}
+
+/// Context for the `record_licence_*` hooks.
+/// Only the licence program can produce a signature for its `licence_authority` PDA.
+#[derive(Accounts)]
+pub struct LicenceHook<'info> {
+    #[account(
+        seeds = [b"licence_authority"],
+        bump,
+        seeds::program = platform_config.licence_program
+    )]
+    pub licence_authority: Signer<'info>,
+
+    #[account(seeds = [b"platform_config"], bump = platform_config.bump)]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(mut)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+}
+
 // Define events to be emitted
 #[event]
 pub struct NewCertificateRegistered {
@@ -128,12 +978,84 @@ pub struct NewCertificateRegistered {
     pub creator: Pubkey,
     pub licence_template_id: u16,
     pub price: u64,
+    pub payment_mint: Pubkey,
+    pub asset_verified: bool,
     // Consider adding metadata_uri_hash if useful for off-chain indexers
 }
 
-// Add new error code
-#[error_code]
-pub enum CreatorClaimCertificateError {
-    #[msg("Price cannot be zero.")]
-    ZeroPriceNotAllowed,
-}
\ No newline at end of file
+#[event]
+pub struct AuthorityTransferred {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
+#[event]
+pub struct MetadataHashUpdated {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub old_metadata_uri_hash: [u8; 32],
+    pub new_metadata_uri_hash: [u8; 32],
+    pub metadata_version: u32,
+}
+
+#[event]
+pub struct ListingUpdated {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub old_price: u64,
+    pub new_price: u64,
+    pub old_payment_mint: Pubkey,
+    pub new_payment_mint: Pubkey,
+    pub old_licence_template_id: u16,
+    pub new_licence_template_id: u16,
+}
+
+#[event]
+pub struct LamportPriceUpdated {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub old_lamport_price: Option<u64>,
+    pub new_lamport_price: Option<u64>,
+}
+
+#[event]
+pub struct RoyaltySplitsAmended {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub previous_royalty_splits: Vec<RoyaltySplit>,
+    pub new_royalty_splits: Vec<RoyaltySplit>,
+}
+
+#[event]
+pub struct CertificateRetired {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub authority: Pubkey,
+    pub closed: bool, // True if the account was closed and rent refunded
+}
+
+#[event]
+pub struct CertificateSuspended {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct CertificateReinstated {
+    pub asset_id: Pubkey,
+    pub certificate_details: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct ConfigUpdated {
+    pub admins: Vec<Pubkey>,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub accepted_payment_mints: Vec<Pubkey>,
+    pub licence_program: Pubkey,
+    pub escrow_period: i64,
+}
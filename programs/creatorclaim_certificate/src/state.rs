@@ -2,6 +2,8 @@ use anchor_lang::prelude::*;
 
 // Constants
 const MAX_RECIPIENTS: usize = 10; // As per rfc-001.md Q4
+const MAX_ADMINS: usize = 5;
+const MAX_PAYMENT_MINTS: usize = 5;
 
 /// Represents a single royalty recipient and their share.
 /// Pubkey + u16 = 32 + 2 = 34 bytes.
@@ -13,33 +15,188 @@ pub struct RoyaltySplit {
     pub share_bps: u16,
 }
 
+/// Opt-in verification of `asset_id_or_mint_pk` performed by `register_certificate`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum AssetVerification {
+    /// No checks; the key is only used as the PDA seed.
+    Unverified,
+    /// The asset is an SPL Token / Token-2022 mint and the creator is its mint authority.
+    Mint,
+    /// The asset is a Bubblegum compressed NFT owned by the creator, proven by a leaf proof.
+    CompressedLeaf(LeafProof),
+}
+
+/// The Bubblegum leaf fields needed to rebuild and verify a compressed asset's leaf hash.
+/// Proof nodes themselves are passed as remaining accounts.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeafProof {
+    /// Current root of the Merkle tree.
+    pub root: [u8; 32],
+    /// Leaf delegate (equal to the owner when no delegate is set).
+    pub delegate: Pubkey,
+    /// Hash of the asset's metadata as stored in the leaf.
+    pub data_hash: [u8; 32],
+    /// Hash of the asset's creators as stored in the leaf.
+    pub creator_hash: [u8; 32],
+    /// Leaf nonce (mint sequence number in the tree).
+    pub nonce: u64,
+    /// Leaf index in the tree.
+    pub index: u32,
+}
+
+/// Lifecycle status of a certificate.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum CertificateStatus {
+    Active,   // Listed; licences can be purchased.
+    Retired,  // Delisted by the authority; no new licences can be purchased.
+    Suspended, // Taken down by a platform admin (e.g., DMCA notice); reversible by an admin.
+}
+
+/// Platform-wide settings shared by the certificate and licence programs.
+/// Seeds: ["platform_config"]
+/// Size Estimation:
+/// - Discriminator: 8 bytes
+/// - Admins Vec: 4 + MAX_ADMINS * 32 = 4 + 160 = 164 bytes
+/// - Treasury: 32 bytes
+/// - Fee (bps): 2 bytes (u16)
+/// - Accepted Payment Mints Vec: 4 + MAX_PAYMENT_MINTS * 32 = 4 + 160 = 164 bytes
+/// - Licence Program: 32 bytes
+/// - Escrow Period: 8 bytes (i64)
+/// - Bump: 1 byte
+/// Total ~ 8 + 164 + 32 + 2 + 164 + 32 + 8 + 1 = 411 bytes
+#[account]
+#[derive(Debug)]
+pub struct PlatformConfig {
+    /// Keys allowed to update this config, suspend certificates and revoke licences.
+    pub admins: Vec<Pubkey>,
+    /// The platform's ops wallet; receives withdrawn platform fees.
+    pub treasury: Pubkey,
+    /// Platform fee taken from each licence purchase, in basis points.
+    pub fee_bps: u16,
+    /// Mints `purchase_licence` accepts as payment.
+    pub accepted_payment_mints: Vec<Pubkey>,
+    /// The licence program whose `licence_authority` PDA may call the licence-count hooks.
+    pub licence_program: Pubkey,
+    /// Seconds licence payments are held in escrow before payout. 0 disables escrow.
+    pub escrow_period: i64,
+    /// Bump seed for the PDA.
+    pub bump: u8,
+}
+
+impl PlatformConfig {
+    pub const LEN: usize = 8 // Discriminator
+        + 4 + (MAX_ADMINS * 32) // admins Vec<Pubkey>
+        + 32 // treasury (Pubkey)
+        + 2 // fee_bps (u16)
+        + 4 + (MAX_PAYMENT_MINTS * 32) // accepted_payment_mints Vec<Pubkey>
+        + 32 // licence_program (Pubkey)
+        + 8 // escrow_period (i64)
+        + 1; // bump (u8)
+
+    pub fn is_admin(&self, key: &Pubkey) -> bool {
+        self.admins.contains(key)
+    }
+
+    /// Validates and applies a full set of config parameters.
+    pub fn apply(&mut self, params: ConfigParams) -> Result<()> {
+        require!(!params.admins.is_empty(), CreatorClaimCertificateError::NoAdmins);
+        require!(
+            params.admins.len() <= MAX_ADMINS,
+            CreatorClaimCertificateError::TooManyAdmins
+        );
+        require!(
+            params.fee_bps <= 10_000,
+            CreatorClaimCertificateError::InvalidFeeBps
+        );
+        require!(
+            params.accepted_payment_mints.len() <= MAX_PAYMENT_MINTS,
+            CreatorClaimCertificateError::TooManyPaymentMints
+        );
+        require!(
+            params.escrow_period >= 0,
+            CreatorClaimCertificateError::InvalidEscrowPeriod
+        );
+
+        self.admins = params.admins;
+        self.treasury = params.treasury;
+        self.fee_bps = params.fee_bps;
+        self.accepted_payment_mints = params.accepted_payment_mints;
+        self.licence_program = params.licence_program;
+        self.escrow_period = params.escrow_period;
+
+        Ok(())
+    }
+}
+
+/// Arguments for `initialize_config` and `update_config`. Replaces every field.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ConfigParams {
+    pub admins: Vec<Pubkey>,
+    pub treasury: Pubkey,
+    pub fee_bps: u16,
+    pub accepted_payment_mints: Vec<Pubkey>,
+    pub licence_program: Pubkey,
+    pub escrow_period: i64,
+}
+
 /// Holds the core details associated with a CreatorClaim certificate (likely a cNFT).
 /// Seeds: ["certificate_details", cnft_mint_or_asset_id]
 /// Size Estimation:
 /// - Discriminator: 8 bytes
 /// - Authority (creator/minter): 32 bytes
+/// - Pending Authority (optional): 1 + 32 = 33 bytes
 /// - Metadata URI Hash (e.g., SHA-256): 32 bytes
+/// - Metadata Version: 4 bytes (u32)
 /// - Licence Template ID: 2 bytes (u16 allows for 65k templates)
 /// - Royalty Splits Vec Prefix: 4 bytes
 /// - Royalty Splits Data: MAX_RECIPIENTS * sizeof(RoyaltySplit) = 10 * 34 = 340 bytes
 /// - Price: 8 bytes (u64)
+/// - Payment Mint: 32 bytes
+/// - Lamport Price (optional): 1 + 8 = 9 bytes (Option<u64>)
+/// - Active Licences: 4 bytes (u32)
+/// - Asset Verified: 1 byte (bool)
+/// - Status: 1 byte (enum)
+/// - Exclusive Licence (optional): 1 + 32 = 33 bytes
+/// - Exclusive Until (optional): 1 + 8 = 9 bytes
 /// - Bump: 1 byte
-/// Total ~ 8 + 32 + 32 + 2 + 4 + 340 + 8 + 1 = 427 bytes
+/// Total ~ 8 + 32 + 33 + 32 + 4 + 2 + 4 + 340 + 8 + 32 + 9 + 4 + 1 + 1 + 33 + 9 + 1 = 553 bytes
 #[account]
 #[derive(Debug)]
 pub struct CertificateDetails {
     /// The authority allowed to manage/update aspects (initially the creator).
     pub authority: Pubkey,
+    /// The authority nominated by `propose_authority`, waiting to call `accept_authority`.
+    /// None when no transfer is in progress.
+    pub pending_authority: Option<Pubkey>,
     /// A hash of the off-chain metadata URI (e.g., Arweave/IPFS link).
     /// Ensures integrity of linked metadata (description, image, etc.).
     pub metadata_uri_hash: [u8; 32],
+    /// Revision number of `metadata_uri_hash`. Starts at 1 and is bumped by every
+    /// `update_metadata_hash`, so licences can be matched to the revision they were bought against.
+    pub metadata_version: u32,
     /// Identifier for the licence template governing this work (maps to PRD Appendix 12.1).
     pub licence_template_id: u16,
     /// Array defining how royalties are split among recipients.
     /// Must sum to 10,000 bps at creation. Limited length for deterministic sizing.
     pub royalty_splits: Vec<RoyaltySplit>,
-    /// The price required to purchase a licence for this work (e.g., in USDC cents).
+    /// The price required to purchase a licence for this work, in base units of `payment_mint`.
     pub price: u64,
+    /// The mint `price` is denominated in (e.g., USDC, EURC or PYUSD). Purchases must pay in it.
+    pub payment_mint: Pubkey,
+    /// The price in lamports for buyers paying in native SOL. None if SOL is not accepted.
+    pub lamport_price: Option<u64>,
+    /// Number of licences for this work that are currently Active.
+    /// Maintained by the licence program through the `record_licence_*` hooks.
+    pub active_licences: u32,
+    /// True if the asset was verified at registration (mint authority or Bubblegum leaf owner).
+    pub asset_verified: bool,
+    /// Whether the certificate is currently purchasable.
+    pub status: CertificateStatus,
+    /// The Licence PDA holding exclusive rights to this work, if any.
+    /// Maintained by the licence program through the `record_*` hooks.
+    pub exclusive_licence: Option<Pubkey>,
+    /// When the exclusive lock lapses. None while locked means the exclusive licence is perpetual.
+    pub exclusive_until: Option<i64>,
     /// Bump seed for the PDA.
     pub bump: u8,
 }
@@ -48,38 +205,144 @@ impl CertificateDetails {
     // Calculate space needed, including Vec discriminator
     pub const LEN: usize = 8 // Discriminator
         + 32 // authority (Pubkey)
+        + 1 + 32 // pending_authority (Option<Pubkey>)
         + 32 // metadata_uri_hash ([u8; 32])
+        + 4 // metadata_version (u32)
         + 2 // licence_template_id (u16)
         + 4 // royalty_splits Vec prefix (u32)
         + (MAX_RECIPIENTS * (32 + 2)) // Max size for Vec<RoyaltySplit> data
         + 8 // price (u64)
+        + 32 // payment_mint (Pubkey)
+        + 1 + 8 // lamport_price (Option<u64>)
+        + 4 // active_licences (u32)
+        + 1 // asset_verified (bool)
+        + 1 // status (enum)
+        + 1 + 32 // exclusive_licence (Option<Pubkey>)
+        + 1 + 8 // exclusive_until (Option<i64>)
         + 1; // bump (u8)
 
-    /// Validates that the royalty splits sum exactly to 10,000 bps
-    /// and do not exceed the maximum number of recipients.
+    /// Validates the listing inputs and populates a freshly created certificate.
+    /// Callers set `asset_verified` and `bump` themselves.
+    pub fn initialize(
+        &mut self,
+        authority: Pubkey,
+        metadata_uri_hash: [u8; 32],
+        licence_template_id: u16,
+        price: u64,
+        payment_mint: Pubkey,
+        royalty_splits: Vec<RoyaltySplit>,
+    ) -> Result<()> {
+        require!(
+            metadata_uri_hash != [0u8; 32],
+            CreatorClaimCertificateError::MissingMetadataHash
+        );
+        require!(price > 0, CreatorClaimCertificateError::ZeroPriceNotAllowed);
+        require!(payment_mint != Pubkey::default(), CreatorClaimCertificateError::MissingPaymentMint);
+        CertificateDetails::validate_splits(&royalty_splits)?;
+
+        self.authority = authority;
+        self.pending_authority = None;
+        self.metadata_uri_hash = metadata_uri_hash;
+        self.metadata_version = 1;
+        self.licence_template_id = licence_template_id;
+        self.price = price;
+        self.payment_mint = payment_mint;
+        self.lamport_price = None;
+        self.active_licences = 0;
+        self.status = CertificateStatus::Active;
+        self.exclusive_licence = None;
+        self.exclusive_until = None;
+        self.royalty_splits = royalty_splits;
+
+        Ok(())
+    }
+
+    /// Returns the licence holding an exclusive lock on this work at `now`, if any.
+    pub fn active_exclusive_licence(&self, now: i64) -> Option<Pubkey> {
+        match self.exclusive_until {
+            Some(until) if now >= until => None,
+            _ => self.exclusive_licence,
+        }
+    }
+
+    /// Checks that `licence` may take (or extend) the exclusive lock at `now`.
+    /// The holder of a live lock may always extend it. A lapsed lock, or a work with
+    /// no lock, can only be taken while no other licence is counted as active; the
+    /// previous holder's own licence stays counted until it is expired or revoked.
+    pub fn check_exclusive_lock(&self, licence: &Pubkey, now: i64) -> Result<()> {
+        match self.active_exclusive_licence(now) {
+            Some(holder) if holder == *licence => Ok(()),
+            Some(_) => err!(CreatorClaimCertificateError::ExclusiveLicenceActive),
+            None => {
+                let own_licence = u32::from(self.exclusive_licence == Some(*licence));
+                require!(
+                    self.active_licences <= own_licence,
+                    CreatorClaimCertificateError::LicencesOutstanding
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Validates that the royalty splits sum exactly to 10,000 bps,
+    /// do not exceed the maximum number of recipients, and that every
+    /// entry has a non-zero share and a distinct beneficiary.
     pub fn validate_splits(splits: &[RoyaltySplit]) -> Result<()> {
         require!(
             splits.len() <= MAX_RECIPIENTS,
             CreatorClaimCertificateError::TooManyRecipients
         );
 
-        let total_bps: u16 = splits.iter().map(|s| s.share_bps).sum();
+        let mut total_bps: u16 = 0;
+        for (i, split) in splits.iter().enumerate() {
+            require!(split.share_bps > 0, CreatorClaimCertificateError::ZeroShare);
+            // At most 10 entries, so a quadratic scan is cheaper than a set.
+            require!(
+                splits[..i].iter().all(|prev| prev.beneficiary != split.beneficiary),
+                CreatorClaimCertificateError::DuplicateBeneficiary
+            );
+            total_bps = total_bps
+                .checked_add(split.share_bps)
+                .ok_or(CreatorClaimCertificateError::RoyaltySumOverflow)?;
+        }
+
         require!(
             total_bps == 10_000,
             CreatorClaimCertificateError::InvalidRoyaltySum
         );
 
-        // Optional: Check for duplicate beneficiaries if needed
-        // let mut unique_beneficiaries = std::collections::HashSet::new();
-        // for split in splits {
-        //     if !unique_beneficiaries.insert(split.beneficiary) {
-        //         // return err!(CreatorClaimCertificateError::DuplicateBeneficiary);
-        //     }
-        // }
-
-
         Ok(())
     }
+
+    /// Returns the share (in bps) currently assigned to `beneficiary`, or 0 if absent.
+    pub fn share_of(splits: &[RoyaltySplit], beneficiary: &Pubkey) -> u16 {
+        splits
+            .iter()
+            .filter(|s| s.beneficiary == *beneficiary)
+            .map(|s| s.share_bps)
+            .sum()
+    }
+}
+
+/// Permanent record that an asset key has been registered. Created alongside the
+/// CertificateDetails and never closed, so once a retired certificate's account is closed
+/// the key cannot be registered again and old licences cannot bind to a new certificate
+/// at the same address.
+/// Seeds: ["asset_registration", asset_id_or_mint_pk.key().as_ref()]
+/// Size Estimation:
+/// - Discriminator: 8 bytes
+/// - Bump: 1 byte
+/// Total ~ 8 + 1 = 9 bytes
+#[account]
+#[derive(Debug)]
+pub struct AssetRegistration {
+    /// Bump seed for the PDA.
+    pub bump: u8,
+}
+
+impl AssetRegistration {
+    pub const LEN: usize = 8 // Discriminator
+        + 1; // bump (u8)
 }
 
 
@@ -92,5 +355,185 @@ pub enum CreatorClaimCertificateError {
     TooManyRecipients,
     #[msg("Metadata hash cannot be empty.")]
     MissingMetadataHash,
+    #[msg("Price cannot be zero.")]
+    ZeroPriceNotAllowed,
+    #[msg("Signer is not the authority of this certificate.")]
+    NotAuthorised,
+    #[msg("No authority transfer is pending for this certificate.")]
+    NoPendingAuthority,
+    #[msg("Signer is not the pending authority of this certificate.")]
+    PendingAuthorityMismatch,
+    #[msg("New metadata hash is identical to the current one.")]
+    MetadataHashUnchanged,
+    #[msg("Metadata version counter overflowed.")]
+    MetadataVersionOverflow,
+    #[msg("Licence template cannot change while licences sold under it are still active.")]
+    LicencesOutstanding,
+    #[msg("Active licence counter overflowed or underflowed.")]
+    LicenceCountOverflow,
+    #[msg("A beneficiary whose share decreases must sign the amendment.")]
+    MissingBeneficiaryConsent,
+    #[msg("Each beneficiary may only appear once in the royalty splits.")]
+    DuplicateBeneficiary,
+    #[msg("Royalty split entries must have a non-zero share.")]
+    ZeroShare,
+    #[msg("Royalty split shares overflowed while summing.")]
+    RoyaltySumOverflow,
+    #[msg("Asset account is not an SPL Token or Token-2022 mint.")]
+    AssetNotMint,
+    #[msg("Creator is not the mint authority of the asset.")]
+    NotMintAuthority,
+    #[msg("Merkle tree and compression program accounts are required for leaf verification.")]
+    MissingCompressionAccounts,
+    #[msg("Asset id does not match the provided Merkle tree and nonce.")]
+    AssetIdMismatch,
+    #[msg("The Merkle tree has no remaining mint capacity.")]
+    TreeCapacity,
+    #[msg("Certificate must be Active for this action.")]
+    CertificateNotActive,
+    #[msg("Certificate is not suspended.")]
+    CertificateNotSuspended,
+    #[msg("Certificate is suspended by the platform.")]
+    CertificateSuspended,
+    #[msg("Platform config must have at least one admin.")]
+    NoAdmins,
+    #[msg("Cannot have more than 5 platform admins.")]
+    TooManyAdmins,
+    #[msg("Platform fee cannot exceed 10,000 basis points.")]
+    InvalidFeeBps,
+    #[msg("Cannot accept more than 5 payment mints.")]
+    TooManyPaymentMints,
+    #[msg("Escrow period cannot be negative.")]
+    InvalidEscrowPeriod,
+    #[msg("Another licence holds exclusive rights to this work.")]
+    ExclusiveLicenceActive,
+    #[msg("A payment mint must be provided for the listing price.")]
+    MissingPaymentMint,
     // Add other specific errors as needed
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(share_bps: u16) -> RoyaltySplit {
+        RoyaltySplit {
+            beneficiary: Pubkey::new_unique(),
+            share_bps,
+        }
+    }
+
+    fn locked_by(licence: Pubkey, exclusive_until: Option<i64>, active_licences: u32) -> CertificateDetails {
+        CertificateDetails {
+            authority: Pubkey::new_unique(),
+            pending_authority: None,
+            metadata_uri_hash: [1; 32],
+            metadata_version: 1,
+            licence_template_id: 1,
+            royalty_splits: vec![split(10_000)],
+            price: 1,
+            payment_mint: Pubkey::new_unique(),
+            lamport_price: None,
+            active_licences,
+            asset_verified: false,
+            status: CertificateStatus::Active,
+            exclusive_licence: Some(licence),
+            exclusive_until,
+            bump: 0,
+        }
+    }
+
+    fn assert_err(splits: &[RoyaltySplit], expected: CreatorClaimCertificateError) {
+        let err = CertificateDetails::validate_splits(splits).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(expected));
+    }
+
+    #[test]
+    fn rejects_zero_recipients() {
+        assert_err(&[], CreatorClaimCertificateError::InvalidRoyaltySum);
+    }
+
+    #[test]
+    fn accepts_single_recipient() {
+        assert!(CertificateDetails::validate_splits(&[split(10_000)]).is_ok());
+    }
+
+    #[test]
+    fn accepts_max_recipients() {
+        let splits: Vec<RoyaltySplit> = (0..MAX_RECIPIENTS).map(|_| split(1_000)).collect();
+        assert!(CertificateDetails::validate_splits(&splits).is_ok());
+    }
+
+    #[test]
+    fn rejects_too_many_recipients() {
+        let mut splits: Vec<RoyaltySplit> = (0..MAX_RECIPIENTS).map(|_| split(1_000)).collect();
+        splits.push(split(1));
+        assert_err(&splits, CreatorClaimCertificateError::TooManyRecipients);
+    }
+
+    #[test]
+    fn rejects_sum_above_10_000() {
+        assert_err(&[split(10_001)], CreatorClaimCertificateError::InvalidRoyaltySum);
+        assert_err(&[split(5_000), split(5_001)], CreatorClaimCertificateError::InvalidRoyaltySum);
+    }
+
+    #[test]
+    fn rejects_sum_below_10_000() {
+        assert_err(&[split(5_000), split(4_999)], CreatorClaimCertificateError::InvalidRoyaltySum);
+    }
+
+    #[test]
+    fn rejects_zero_share() {
+        assert_err(&[split(10_000), split(0)], CreatorClaimCertificateError::ZeroShare);
+    }
+
+    #[test]
+    fn rejects_duplicate_beneficiary() {
+        let first = split(5_000);
+        let duplicate = RoyaltySplit {
+            beneficiary: first.beneficiary,
+            share_bps: 5_000,
+        };
+        assert_err(&[first, duplicate], CreatorClaimCertificateError::DuplicateBeneficiary);
+    }
+
+    #[test]
+    fn rejects_overflowing_sum() {
+        // Ten large entries would overflow a u16 accumulator.
+        let splits: Vec<RoyaltySplit> = (0..MAX_RECIPIENTS).map(|_| split(u16::MAX / 4)).collect();
+        assert_err(&splits, CreatorClaimCertificateError::RoyaltySumOverflow);
+    }
+
+    #[test]
+    fn holder_extends_a_live_lock() {
+        let holder = Pubkey::new_unique();
+        assert!(locked_by(holder, Some(100), 1).check_exclusive_lock(&holder, 50).is_ok());
+    }
+
+    #[test]
+    fn live_lock_refuses_other_licences() {
+        let certificate = locked_by(Pubkey::new_unique(), None, 1);
+        let err = certificate.check_exclusive_lock(&Pubkey::new_unique(), 50).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimCertificateError::ExclusiveLicenceActive));
+    }
+
+    #[test]
+    fn holder_retakes_a_lapsed_lock_only_while_alone() {
+        let holder = Pubkey::new_unique();
+        assert!(locked_by(holder, Some(100), 1).check_exclusive_lock(&holder, 150).is_ok());
+
+        // Another licence was sold after the lock lapsed
+        let err = locked_by(holder, Some(100), 2).check_exclusive_lock(&holder, 150).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimCertificateError::LicencesOutstanding));
+    }
+
+    #[test]
+    fn newcomer_takes_a_lapsed_lock_only_once_the_holder_has_ended() {
+        let certificate = locked_by(Pubkey::new_unique(), Some(100), 1);
+        let err = certificate.check_exclusive_lock(&Pubkey::new_unique(), 150).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimCertificateError::LicencesOutstanding));
+
+        let certificate = locked_by(Pubkey::new_unique(), Some(100), 0);
+        assert!(certificate.check_exclusive_lock(&Pubkey::new_unique(), 150).is_ok());
+    }
+}
@@ -1,21 +1,22 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Mint, Token, TokenAccount, Transfer};
-use anchor_spl::token_2022::{self, Mint as Token2022Mint, Token as Token2022Token, TokenAccount as Token2022TokenAccount, Transfer as Token2022Transfer};
-use spl_token_2022::ID as TOKEN_2022_PROGRAM_ID;
+use anchor_spl::token::spl_token::native_mint::ID as NATIVE_MINT;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 
 // Make state and errors available
 mod state;
 use state::*;
 
+mod payment;
+use payment::{
+    bps_of, close_token_account, distribute_royalties, pay_fee_and_royalties, pay_fee_and_royalties_lamports,
+    payment_total, transfer_lamports, transfer_tokens, transfer_tokens_net, transfer_total,
+};
+
 // Import the certificate program crate and its state
 // Requires adding `creatorclaim_certificate = { path = "../creatorclaim_certificate", features = ["cpi"] }` to Cargo.toml
 // and adding the program declaration to Anchor.toml
-use creatorclaim_certificate::state::CertificateDetails;
-
-// Placeholder for actual Certificate Program ID
-const CERTIFICATE_PROGRAM_ID: &str = "CERTxxxxxxxxxxxxxxxxxx";
-// Placeholder for potential Admin Pubkey
-const ADMIN_PUBKEY: &str = "Adm1xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx"; // Replace with actual Admin Pubkey
+use creatorclaim_certificate::state::{CertificateDetails, CertificateStatus, PlatformConfig};
+use creatorclaim_certificate::program::CreatorclaimCertificate;
 
 declare_id!("LICxxxxxxxxxxxxxxxxxx"); // Replace with actual Program ID after deploy
 
@@ -35,139 +36,432 @@ security_txt! {
 pub mod creatorclaim_licence {
     use super::*;
 
-    /// Instruction to purchase a licence for a creative work using Token-2022.
-    /// This creates the Licence PDA, transfers payment (USDC via Token-242),
-    /// and relies on the Token-2022 Transfer Fee extension (transfer hook)
-    /// to automatically distribute royalties based on the mint's configuration.
+    /// Instruction to purchase a licence for a creative work using SPL Token or Token-2022.
+    /// This creates the Licence PDA, skims the platform fee (`PlatformConfig.fee_bps`)
+    /// into the Treasury, and pays each royalty beneficiary its bps share of the rest
+    /// directly, following the certificate's `royalty_splits`.
     ///
     /// Args:
     ///     ctx: Context containing accounts needed.
-    ///     purchase_price: The total price including royalties and platform fee.
-    ///     expiry_timestamp: Optional expiry for time-limited licences.
+    ///     max_price: The most the buyer will pay, including royalties, platform fee and any
+    ///                Token-2022 transfer fees. The certificate's current price is charged
+    ///                if the total is within this bound.
     ///
     /// Accounts:
     ///     buyer: The signer purchasing the licence.
-    ///     buyer_token_account: The buyer's Token-2022 account to pay from.
+    ///     buyer_token_account: The buyer's `payment_mint` token account to pay from.
     ///     licence: The Licence PDA to be initialized.
     ///     certificate_details: The CertificateDetails account for the work being licensed.
-    ///     payment_mint: The Token-2022 mint address configured with the Transfer Fee extension.
-    ///     token_program: The Token-2022 Program.
+    ///     licence_template: The certificate's LicenceTemplate; sets the licence term.
+    ///     payment_mint: The SPL Token or Token-2022 mint the price is paid in.
+    ///                   Must be the certificate's `payment_mint` and accepted by the platform.
+    ///     token_program: The token program owning `payment_mint`.
     ///     system_program: System program.
-    ///     remaining_accounts: Must include all token accounts that will receive fees
-    ///                         (royalty recipients, platform treasury) as specified in the
-    ///                         Transfer Fee extension config on the `payment_mint`.
-    ///                         These are required by the transfer hook.
-    pub fn purchase_licence(
-        ctx: Context<PurchaseLicence>,
-        purchase_price: u64,
-        expiry_timestamp: Option<i64>,
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (accepted payment mints, fee).
+    ///     treasury_token_account: The Treasury PDA for `payment_mint`. Seeds: ["treasury", payment_mint]
+    ///     remaining_accounts: One `payment_mint` token account per royalty split, in the
+    ///                         same order as `royalty_splits`, each owned by that split's
+    ///                         beneficiary.
+    pub fn purchase_licence<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseLicence<'info>>,
+        max_price: u64,
     ) -> Result<()> {
         msg!("Purchasing licence for certificate: {}", ctx.accounts.certificate_details.key());
-        msg!("Buyer: {}, Price: {}, Expiry: {:?}", ctx.accounts.buyer.key(), purchase_price, expiry_timestamp);
+        msg!("Buyer: {}, Max price: {}", ctx.accounts.buyer.key(), max_price);
 
         let licence = &mut ctx.accounts.licence;
         let clock = Clock::get()?;
         let buyer = &ctx.accounts.buyer;
         let token_program = &ctx.accounts.token_program;
         let buyer_token_account = &ctx.accounts.buyer_token_account;
-        let certificate_details_account_info = &ctx.accounts.certificate_details;
-
-        // 1. Validate purchase_price against expected price
-        // Ensure the provided certificate_details account is owned by the correct program
-        require_keys_eq!(
-            certificate_details_account_info.owner.key(),
-            CERTIFICATE_PROGRAM_ID.parse::<Pubkey>().expect("Invalid Certificate Program ID constant"),
-            CreatorClaimLicenceError::CertificateMismatch
-        );
-
-        // Load the certificate details data
-        // Using AccountLoader for efficiency if state is large, or direct deserialization
-        let cert_details_data = Account::<CertificateDetails>::try_from(certificate_details_account_info)?;
 
-        // --- Price determination logic ---
-        // Read the expected price directly from the CertificateDetails account.
-        let expected_price = cert_details_data.price;
-        msg!("Expected price from certificate details: {}", expected_price);
-        // --- End Price determination ---
-
-        require!(purchase_price == expected_price, CreatorClaimLicenceError::IncorrectPrice);
+        // 1. Validate the certificate and payment mint, and price the licence within max_price
+        let cert_details_data = &ctx.accounts.certificate_details;
+        let purchase_price = validate_purchase(
+            &licence.key(),
+            cert_details_data,
+            &ctx.accounts.licence_template,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.payment_mint,
+            max_price,
+            false,
+        )?;
+        require!(
+            ctx.accounts.platform_config.escrow_period == 0,
+            CreatorClaimLicenceError::EscrowRequired
+        );
         msg!("Purchase price validated.");
 
-        // 2. Perform payment transfer using Token-2022
-        //    The standard `transfer` CPI is used. The Token-2022 program's transfer hook,
-        //    configured on the mint (Transfer Fee extension), intercepts this call.
-        //    It automatically deducts fees based on the mint's config and transfers them
-        //    to the accounts provided in ctx.remaining_accounts.
-        //    The remaining amount is transferred to the primary destination account
-        //    (which needs to be determined - often a central escrow or the first recipient).
-        //    For simplicity, we might initially transfer to a placeholder or treasury,
-        //    assuming the hook handles the *actual* distribution.
-        msg!("Initiating Token-2022 transfer of {} tokens from buyer {}.",
-             purchase_price, buyer_token_account.key());
-        msg!("Transfer hook expected to distribute fees to accounts in remaining_accounts.");
-
-        // *** Crucial: The recipient account (`to`) in this primary transfer matters. ***
-        // It receives the amount *after* fees are deducted by the hook.
-        // This should likely be a designated recipient or escrow account.
-        // For now, using a placeholder recipient from remaining_accounts if available,
-        // otherwise erroring or using treasury. THIS NEEDS FINAL DESIGN.
-        let primary_recipient_account_info = ctx.remaining_accounts.get(0)
-            .ok_or_else(|| CreatorClaimLicenceError::MissingRecipientAccount)?;
-        // Basic validation: Ensure recipient is not the zero address or burner address
-        // More robust checks might involve ensuring it's initialized/owned by Token-2022.
-        require!(primary_recipient_account_info.key() != Pubkey::default(), CreatorClaimLicenceError::InvalidRecipientAccount);
-        msg!("Primary recipient account validated (basic check): {}", primary_recipient_account_info.key());
-
-        let transfer_instruction = Transfer {
-            from: buyer_token_account.to_account_info(),
-            to: primary_recipient_account_info.to_account_info(),
-            authority: buyer.to_account_info(),
-        };
+        // 2. Skim the platform fee into the Treasury, then pay each royalty
+        //    beneficiary its share of the rest directly. The buyer covers any
+        //    Token-2022 transfer fee on top, so every leg arrives in full.
+        msg!("Paying {} tokens from buyer {}.", purchase_price, buyer_token_account.key());
+        let (platform_fee, transfer_fee) = pay_fee_and_royalties(
+            &token_program.to_account_info(),
+            &buyer_token_account.to_account_info(),
+            &buyer.to_account_info(),
+            &ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            &cert_details_data.royalty_splits,
+            &ctx.accounts.payment_mint,
+            ctx.accounts.platform_config.fee_bps,
+            purchase_price,
+            true,
+            &[],
+        )?;
+        msg!("Royalties distributed ({} paid in transfer fees).", transfer_fee);
 
-        // IMPORTANT: Pass ALL recipient accounts from remaining_accounts to the CPI context.
-        // The transfer hook requires them.
-        let mut cpi_ctx = CpiContext::new(token_program.to_account_info(), transfer_instruction);
-        cpi_ctx = cpi_ctx.with_remaining_accounts(ctx.remaining_accounts.to_vec());
-
-        token_2022::transfer(cpi_ctx, purchase_price)?;
-        msg!("Token-2022 transfer initiated successfully. Hook should have processed fees.");
-
-        // 3. Populate the Licence PDA data
-        licence.certificate_details = ctx.accounts.certificate_details.key();
-        licence.buyer = ctx.accounts.buyer.key();
-        licence.purchase_price = purchase_price;
-        licence.purchase_timestamp = clock.unix_timestamp;
-        licence.expiry_timestamp = expiry_timestamp;
-        licence.status = LicenceStatus::Active;
+        // 3. Populate the Licence PDA data; the template decides the term
+        licence.initialize(
+            ctx.accounts.certificate_details.key(),
+            ctx.accounts.buyer.key(),
+            purchase_price,
+            ctx.accounts.payment_mint.key(),
+            &ctx.accounts.licence_template,
+            clock.unix_timestamp,
+        )?;
+        licence.transfer_fee_paid = transfer_fee;
         licence.bump = ctx.bumps.licence;
 
+        record_new_licence(
+            &ctx.accounts.certificate_program,
+            &ctx.accounts.licence_authority,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.certificate_details,
+            licence,
+            ctx.accounts.licence_template.exclusive,
+            ctx.bumps.licence_authority,
+        )?;
+
         // 4. Emit event
         emit!(LicencePurchased {
             licence_pda: licence.key(),
             certificate_details: licence.certificate_details,
             buyer: licence.buyer,
             purchase_price,
+            max_price,
+            payment_mint: licence.payment_mint,
+            platform_fee,
+            transfer_fee,
             purchase_timestamp: licence.purchase_timestamp,
+            expiry_timestamp: licence.expiry_timestamp,
+            licence_template_id: licence.licence_template_id,
+            escrow_release_at: None,
         });
 
         msg!("Licence PDA created: {}", licence.key());
         Ok(())
     }
 
+    /// Instruction to purchase a licence in native SOL, for certificates that declare a
+    /// `lamport_price`. Mirrors `purchase_licence`: the platform fee goes to the
+    /// `PlatformConfig.treasury` wallet and the rest is paid to each royalty beneficiary's
+    /// wallet through System Program transfers. The Licence records `NATIVE_MINT` as its
+    /// payment mint and the price in lamports.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     max_price: The most the buyer will pay in lamports, including royalties and platform
+    ///                fee. The certificate's current `lamport_price` is charged if it is within this bound.
+    ///
+    /// Accounts:
+    ///     buyer: The signer purchasing the licence and paying in SOL.
+    ///     licence: The Licence PDA to be initialized.
+    ///     certificate_details: The CertificateDetails account for the work being licensed.
+    ///     licence_template: The certificate's LicenceTemplate; sets the licence term.
+    ///     system_program: System program.
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (fee, treasury).
+    ///     treasury: The `PlatformConfig.treasury` wallet receiving the platform fee.
+    ///     remaining_accounts: One writable wallet per royalty split, in the same order as
+    ///                         `royalty_splits`, each equal to that split's beneficiary.
+    pub fn purchase_licence_with_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, PurchaseLicenceWithSol<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        let certificate_details = &ctx.accounts.certificate_details;
+        validate_listing(&ctx.accounts.licence.key(), certificate_details, &ctx.accounts.licence_template)?;
+        let purchase_price = certificate_details
+            .lamport_price
+            .ok_or(CreatorClaimLicenceError::LamportsNotAccepted)?;
+        require!(purchase_price <= max_price, CreatorClaimLicenceError::SlippageExceeded);
+        // Lamports are paid out immediately; there is no SOL escrow.
+        require!(
+            ctx.accounts.platform_config.escrow_period == 0,
+            CreatorClaimLicenceError::EscrowRequired
+        );
+
+        let platform_fee = pay_fee_and_royalties_lamports(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            ctx.remaining_accounts,
+            &certificate_details.royalty_splits,
+            ctx.accounts.platform_config.fee_bps,
+            purchase_price,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let licence = &mut ctx.accounts.licence;
+        licence.initialize(
+            ctx.accounts.certificate_details.key(),
+            ctx.accounts.buyer.key(),
+            purchase_price,
+            NATIVE_MINT,
+            &ctx.accounts.licence_template,
+            now,
+        )?;
+        licence.bump = ctx.bumps.licence;
+
+        record_new_licence(
+            &ctx.accounts.certificate_program,
+            &ctx.accounts.licence_authority,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.certificate_details,
+            licence,
+            ctx.accounts.licence_template.exclusive,
+            ctx.bumps.licence_authority,
+        )?;
+
+        emit!(LicencePurchased {
+            licence_pda: licence.key(),
+            certificate_details: licence.certificate_details,
+            buyer: licence.buyer,
+            purchase_price,
+            max_price,
+            payment_mint: licence.payment_mint,
+            platform_fee,
+            transfer_fee: 0,
+            purchase_timestamp: licence.purchase_timestamp,
+            expiry_timestamp: licence.expiry_timestamp,
+            licence_template_id: licence.licence_template_id,
+            escrow_release_at: None,
+        });
+
+        msg!("Licence PDA created: {} (paid {} lamports)", licence.key(), purchase_price);
+        Ok(())
+    }
+
+    /// Instruction to purchase a licence with the payment held in escrow.
+    /// The full price sits in a per-licence escrow PDA for `PlatformConfig.escrow_period`
+    /// seconds, so it can be refunded if the licence is revoked (e.g. a DMCA takedown)
+    /// before `release_escrow` pays it out.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     max_price: The most the buyer will pay, including royalties, platform fee and any
+    ///                Token-2022 transfer fees. The certificate's current price is charged
+    ///                if the total is within this bound.
+    ///
+    /// Accounts:
+    ///     buyer: The signer purchasing the licence.
+    ///     buyer_token_account: The buyer's `payment_mint` token account to pay from.
+    ///     licence: The Licence PDA to be initialized.
+    ///     escrow_token_account: The escrow PDA to be initialized. Seeds: ["escrow", licence]
+    ///     certificate_details: The CertificateDetails account for the work being licensed.
+    ///     licence_template: The certificate's LicenceTemplate; sets the licence term.
+    ///     payment_mint: The SPL Token or Token-2022 mint the price is paid in.
+    ///     token_program: The token program owning `payment_mint`.
+    ///     system_program: System program.
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (accepted payment mints, escrow period).
+    pub fn purchase_licence_escrowed(
+        ctx: Context<PurchaseLicenceEscrowed>,
+        max_price: u64,
+    ) -> Result<()> {
+        msg!("Purchasing escrowed licence for certificate: {}", ctx.accounts.certificate_details.key());
+
+        let purchase_price = validate_purchase(
+            &ctx.accounts.licence.key(),
+            &ctx.accounts.certificate_details,
+            &ctx.accounts.licence_template,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.payment_mint,
+            max_price,
+            true,
+        )?;
+
+        // The buyer covers any transfer fee so the escrow holds the full price
+        let transfer_fee = transfer_tokens_net(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.escrow_token_account.to_account_info(),
+            &ctx.accounts.buyer.to_account_info(),
+            purchase_price,
+            &[],
+        )?;
+
+        let clock = Clock::get()?;
+        let escrow_release_at = clock
+            .unix_timestamp
+            .checked_add(ctx.accounts.platform_config.escrow_period)
+            .ok_or(CreatorClaimLicenceError::EscrowLocked)?;
+
+        let licence = &mut ctx.accounts.licence;
+        licence.initialize(
+            ctx.accounts.certificate_details.key(),
+            ctx.accounts.buyer.key(),
+            purchase_price,
+            ctx.accounts.payment_mint.key(),
+            &ctx.accounts.licence_template,
+            clock.unix_timestamp,
+        )?;
+        licence.transfer_fee_paid = transfer_fee;
+        licence.escrow_release_at = Some(escrow_release_at);
+        licence.bump = ctx.bumps.licence;
+
+        record_new_licence(
+            &ctx.accounts.certificate_program,
+            &ctx.accounts.licence_authority,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.certificate_details,
+            licence,
+            ctx.accounts.licence_template.exclusive,
+            ctx.bumps.licence_authority,
+        )?;
+
+        // The platform fee is taken when the escrow is released.
+        emit!(LicencePurchased {
+            licence_pda: licence.key(),
+            certificate_details: licence.certificate_details,
+            buyer: licence.buyer,
+            purchase_price,
+            max_price,
+            payment_mint: licence.payment_mint,
+            platform_fee: 0,
+            transfer_fee: licence.transfer_fee_paid,
+            purchase_timestamp: licence.purchase_timestamp,
+            expiry_timestamp: licence.expiry_timestamp,
+            licence_template_id: licence.licence_template_id,
+            escrow_release_at: licence.escrow_release_at,
+        });
+
+        msg!("Licence PDA created: {}, escrow releases at {}", licence.key(), escrow_release_at);
+        Ok(())
+    }
+
+    /// Permissionless crank paying out an escrowed licence once its dispute window has ended.
+    /// Skims the platform fee into the Treasury, pays the royalty splits from escrow,
+    /// then closes the escrow account and returns its rent to the buyer. Any Token-2022
+    /// transfer fee on these payouts is withheld from what the recipients receive.
+    /// If the licence was revoked or expired while escrowed, its slot in the
+    /// certificate's active licence count is released here.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///
+    /// Accounts:
+    ///     licence: The escrowed Licence PDA.
+    ///     buyer: The licence buyer, receiving the escrow account's rent.
+    ///     certificate_details: The CertificateDetails account of the licence (royalty splits).
+    ///     platform_config: The certificate program's PlatformConfig PDA (fee).
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     payment_mint: The mint the price was paid in.
+    ///     escrow_token_account: The licence's escrow PDA. Seeds: ["escrow", licence]
+    ///     treasury_token_account: The Treasury PDA for `payment_mint`. Seeds: ["treasury", payment_mint]
+    ///     token_program: The token program owning `payment_mint`.
+    ///     remaining_accounts: One `payment_mint` token account per royalty split, in the
+    ///                         same order as `royalty_splits`, each owned by that split's
+    ///                         beneficiary.
+    pub fn release_escrow<'info>(ctx: Context<'_, '_, '_, 'info, ReleaseEscrow<'info>>) -> Result<()> {
+        let licence = &mut ctx.accounts.licence;
+        let release_at = licence.escrow_release_at.ok_or(CreatorClaimLicenceError::NotEscrowed)?;
+        require!(
+            Clock::get()?.unix_timestamp >= release_at,
+            CreatorClaimLicenceError::EscrowLocked
+        );
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let escrow = ctx.accounts.escrow_token_account.to_account_info();
+        let amount = ctx.accounts.escrow_token_account.amount;
+        let licence_key = licence.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"escrow",
+            licence_key.as_ref(),
+            &[ctx.bumps.escrow_token_account],
+        ]];
+
+        // The escrow only holds the purchase price, so recipients bear any transfer fee here
+        let (platform_fee, _) = pay_fee_and_royalties(
+            &token_program,
+            &escrow,
+            &escrow,
+            &ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            &ctx.accounts.certificate_details.royalty_splits,
+            &ctx.accounts.payment_mint,
+            ctx.accounts.platform_config.fee_bps,
+            amount,
+            false,
+            signer_seeds,
+        )?;
+        close_token_account(
+            &token_program,
+            &escrow,
+            &ctx.accounts.buyer.to_account_info(),
+            signer_seeds,
+        )?;
+
+        licence.escrow_release_at = None;
+
+        // A licence that ended while escrowed kept its slot until now
+        if licence.status != LicenceStatus::Active {
+            record_licence_released(
+                &ctx.accounts.certificate_program,
+                &ctx.accounts.licence_authority,
+                &ctx.accounts.platform_config,
+                &ctx.accounts.certificate_details,
+                licence_key,
+                ctx.bumps.licence_authority,
+            )?;
+        }
+
+        msg!("Escrow released for licence {}: {} paid, {} platform fee", licence_key, amount, platform_fee);
+        emit!(EscrowReleased {
+            licence_pda: licence_key,
+            certificate_details: licence.certificate_details,
+            amount,
+            platform_fee,
+        });
+        Ok(())
+    }
+
     // TODO: Add revoke_licence instruction
     /// Instruction to revoke an existing licence.
     /// This can typically be called by the original content creator/authority
     /// or a designated platform admin in case of DMCA or other issues.
+    /// Revoking an escrowed licence inside its dispute window refunds the buyer in full
+    /// from escrow. Otherwise the revoker may refund `refund_bps` of the purchase price
+    /// from their own token account, or in lamports from their wallet for SOL licences.
     ///
     /// Args:
     ///     ctx: Context containing accounts needed.
+    ///     refund_bps: Share of `purchase_price` to refund from the revoker, in basis points.
+    ///                 Ignored when the refund comes from escrow.
     ///
     /// Accounts:
     ///     revoker: The signer authorized to revoke the licence.
     ///     licence: The Licence PDA to be revoked (must be mutable).
     ///     certificate_details: The corresponding CertificateDetails account.
     ///                         Used to verify the revoker's authority.
-    pub fn revoke_licence(ctx: Context<RevokeLicence>) -> Result<()> {
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (admin set).
+    ///     escrow_token_account: Optional. The licence's escrow PDA; required inside the dispute window.
+    ///     buyer_token_account: Optional. The licence holder's token account receiving the refund.
+    ///     buyer: Optional. The licence buyer, receiving the escrow account's rent.
+    ///     revoker_token_account: Optional. The revoker's token account funding a `refund_bps` refund.
+    ///     payment_mint: Optional. The licence's payment mint, required for token refunds.
+    ///     token_program: Optional. The token program owning `payment_mint`, required for token refunds.
+    ///     holder: Optional. The licence holder's wallet, receiving a SOL refund.
+    ///     system_program: Optional. System program, required for SOL refunds.
+    pub fn revoke_licence(ctx: Context<RevokeLicence>, refund_bps: u16) -> Result<()> {
         msg!("Revoking licence PDA: {}", ctx.accounts.licence.key());
 
         let licence = &mut ctx.accounts.licence;
@@ -177,43 +471,680 @@ pub mod creatorclaim_licence {
 
         // --- Authorization Check ---
         let is_authority = revoker.key() == cert_details_data.authority;
-        // Allow if revoker is the certificate authority OR the designated admin
-        let admin_pubkey = ADMIN_PUBKEY.parse::<Pubkey>().expect("Invalid Admin Pubkey constant");
-        let is_admin = revoker.key() == admin_pubkey;
+        // Allow if revoker is the certificate authority OR one of the platform admins
+        let is_admin = ctx.accounts.platform_config.is_admin(&revoker.key());
 
         require!(is_authority || is_admin, CreatorClaimLicenceError::UnauthorizedRevoker);
         msg!("Revoker {} authorized (is_authority: {}, is_admin: {}).", revoker.key(), is_authority, is_admin);
 
+        require!(refund_bps <= 10_000, CreatorClaimLicenceError::InvalidRefundBps);
+
         // --- Check Licence Status ---
         require!(licence.status == LicenceStatus::Active, CreatorClaimLicenceError::LicenceRevoked); // Or LicenceExpired?
 
         // --- Update Status ---
+        let was_counted = licence.counts_as_active();
         licence.status = LicenceStatus::Revoked;
         msg!("Licence status updated to Revoked.");
 
+        // --- Refund escrow inside the dispute window ---
+        let mut refunded_amount = 0;
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(release_at) = licence.escrow_release_at.filter(|release_at| now < *release_at) {
+            let (Some(escrow), Some(buyer_token_account), Some(buyer), Some(payment_mint), Some(token_program)) = (
+                &ctx.accounts.escrow_token_account,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.buyer,
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(CreatorClaimLicenceError::MissingEscrowAccounts);
+            };
+
+            let licence_key = licence.key();
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                b"escrow",
+                licence_key.as_ref(),
+                &[ctx.bumps.escrow_token_account],
+            ]];
+            let amount = escrow.amount;
+            transfer_tokens(
+                &token_program.to_account_info(),
+                &escrow.to_account_info(),
+                payment_mint,
+                &buyer_token_account.to_account_info(),
+                &escrow.to_account_info(),
+                amount,
+                signer_seeds,
+            )?;
+            close_token_account(
+                &token_program.to_account_info(),
+                &escrow.to_account_info(),
+                &buyer.to_account_info(),
+                signer_seeds,
+            )?;
+            licence.escrow_release_at = None;
+            refunded_amount = amount;
+
+            msg!("Refunded {} from escrow to buyer {} (window open until {})", amount, licence.buyer, release_at);
+            emit!(EscrowRefunded {
+                licence_pda: licence_key,
+                buyer: licence.buyer,
+                amount,
+            });
+        } else if refund_bps > 0 && licence.payment_mint == NATIVE_MINT {
+            // --- Revoker-funded refund of a SOL licence ---
+            let (Some(holder), Some(system_program)) = (&ctx.accounts.holder, &ctx.accounts.system_program) else {
+                return err!(CreatorClaimLicenceError::MissingRefundAccounts);
+            };
+
+            refunded_amount = bps_of(licence.purchase_price, refund_bps)?;
+            if refunded_amount > 0 {
+                transfer_lamports(
+                    &system_program.to_account_info(),
+                    &revoker.to_account_info(),
+                    &holder.to_account_info(),
+                    refunded_amount,
+                )?;
+                msg!("Revoker refunded {} lamports ({} bps) to holder {}", refunded_amount, refund_bps, licence.holder);
+            }
+        } else if refund_bps > 0 {
+            // --- Revoker-funded refund ---
+            let (Some(revoker_token_account), Some(buyer_token_account), Some(payment_mint), Some(token_program)) = (
+                &ctx.accounts.revoker_token_account,
+                &ctx.accounts.buyer_token_account,
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(CreatorClaimLicenceError::MissingRefundAccounts);
+            };
+            require_keys_eq!(
+                revoker_token_account.mint,
+                buyer_token_account.mint,
+                CreatorClaimLicenceError::InvalidRecipientAccount
+            );
+
+            refunded_amount = bps_of(licence.purchase_price, refund_bps)?;
+            if refunded_amount > 0 {
+                // The revoker covers any transfer fee so the holder nets the full refund
+                transfer_tokens_net(
+                    &token_program.to_account_info(),
+                    &revoker_token_account.to_account_info(),
+                    payment_mint,
+                    &buyer_token_account.to_account_info(),
+                    &revoker.to_account_info(),
+                    refunded_amount,
+                    &[],
+                )?;
+                msg!("Revoker refunded {} ({} bps) to holder {}", refunded_amount, refund_bps, licence.holder);
+            }
+        }
+
+        // Sublicences were never counted; a licence whose escrow is still held after the
+        // dispute window keeps its slot until `release_escrow` pays it out
+        if was_counted && !licence.counts_as_active() {
+            record_licence_released(
+                &ctx.accounts.certificate_program,
+                &ctx.accounts.licence_authority,
+                &ctx.accounts.platform_config,
+                &ctx.accounts.certificate_details,
+                licence.key(),
+                ctx.bumps.licence_authority,
+            )?;
+        }
+
         // --- Emit Event ---
         emit!(LicenceRevoked {
             licence_pda: licence.key(),
             certificate_details: licence.certificate_details, // Already stored on licence
             revoker: revoker.key(),
+            refunded_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Permissionless crank marking a time-limited licence Expired once its
+    /// `expiry_timestamp` has passed, releasing its slot in the certificate's
+    /// active licence count. Optionally closes the licence and returns its rent to the buyer.
+    /// A licence with escrow still pending keeps its slot until `release_escrow`.
+    /// A licence that has already ended (Expired or Revoked) can be passed with `close`
+    /// set to reclaim its rent; its slot was released when it ended, so no hook runs.
+    /// The certificate may already have been closed by `retire_certificate`, in which
+    /// case there is no count left to release and the hook is skipped.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     close: Whether to close the Licence account after expiring it.
+    ///
+    /// Accounts:
+    ///     licence: The Licence PDA to expire.
+    ///     buyer: The licence buyer (for a sublicence, its issuer), receiving the rent if `close` is set.
+    ///     certificate_details: The CertificateDetails account of the licence, possibly already closed.
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA.
+    pub fn expire_licence(ctx: Context<ExpireLicence>, close: bool) -> Result<()> {
+        let licence = &mut ctx.accounts.licence;
+        let already_ended = match licence.status {
+            LicenceStatus::Active => false,
+            LicenceStatus::Revoked => {
+                require!(close, CreatorClaimLicenceError::LicenceRevoked);
+                true
+            }
+            LicenceStatus::Expired => {
+                require!(close, CreatorClaimLicenceError::LicenceExpired);
+                true
+            }
+        };
+
+        if already_ended {
+            // The escrow is keyed off the licence, so it must be paid out before the licence goes away.
+            require!(licence.escrow_release_at.is_none(), CreatorClaimLicenceError::EscrowPending);
+            let status = licence.status.clone();
+            licence.close(ctx.accounts.buyer.to_account_info())?;
+            msg!("Ended licence {} closed, rent returned to buyer {}", ctx.accounts.licence.key(), ctx.accounts.buyer.key());
+            emit!(LicenceClosed {
+                licence_pda: ctx.accounts.licence.key(),
+                certificate_details: ctx.accounts.licence.certificate_details,
+                buyer: ctx.accounts.licence.buyer,
+                status,
+            });
+            return Ok(());
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let expiry_timestamp = licence.expiry_timestamp.ok_or(CreatorClaimLicenceError::LicenceNotExpired)?;
+        require!(now > expiry_timestamp, CreatorClaimLicenceError::LicenceNotExpired);
+
+        let was_counted = licence.counts_as_active();
+        licence.status = LicenceStatus::Expired;
+        msg!("Licence {} expired at {}", licence.key(), expiry_timestamp);
+
+        // Sublicences were never counted; an escrowed licence keeps its slot until released.
+        // A closed certificate has no count left to update.
+        let certificate_details = &ctx.accounts.certificate_details;
+        let certificate_open = certificate_details.owner == &creatorclaim_certificate::ID && !certificate_details.data_is_empty();
+        if was_counted && !licence.counts_as_active() && certificate_open {
+            record_licence_released(
+                &ctx.accounts.certificate_program,
+                &ctx.accounts.licence_authority,
+                &ctx.accounts.platform_config,
+                certificate_details,
+                licence.key(),
+                ctx.bumps.licence_authority,
+            )?;
+        }
+
+        if close {
+            // The escrow is keyed off the licence, so it must be paid out before the licence goes away.
+            require!(licence.escrow_release_at.is_none(), CreatorClaimLicenceError::EscrowPending);
+            licence.close(ctx.accounts.buyer.to_account_info())?;
+            msg!("Licence account closed, rent returned to buyer {}", ctx.accounts.buyer.key());
+        }
+
+        emit!(LicenceExpired {
+            licence_pda: ctx.accounts.licence.key(),
+            certificate_details: ctx.accounts.licence.certificate_details,
+            buyer: ctx.accounts.licence.buyer,
+            expiry_timestamp,
+            closed: close,
+        });
+        Ok(())
+    }
+
+    /// Instruction for a licence holder to renew a time-limited licence.
+    /// Takes payment again (platform fee plus royalty splits), pushes `expiry_timestamp`
+    /// forward by the template's term from the later of now and the current expiry,
+    /// and reactivates the licence if it had already been marked Expired.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     max_price: The most the holder will pay, including any Token-2022 transfer fees;
+    ///                the certificate's current price is charged.
+    ///
+    /// Accounts:
+    ///     holder: The current licence holder (signer).
+    ///     holder_token_account: The holder's `payment_mint` token account to pay from.
+    ///     licence: The Licence PDA to renew.
+    ///     certificate_details: The CertificateDetails account of the licence.
+    ///     licence_template: The LicenceTemplate the licence was issued under.
+    ///     payment_mint: The SPL Token or Token-2022 mint the price is paid in.
+    ///     token_program: The token program owning `payment_mint`.
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (accepted payment mints, fee).
+    ///     treasury_token_account: The Treasury PDA for `payment_mint`. Seeds: ["treasury", payment_mint]
+    ///     remaining_accounts: One `payment_mint` token account per royalty split, in the
+    ///                         same order as `royalty_splits`, each owned by that split's
+    ///                         beneficiary.
+    pub fn renew_licence<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewLicence<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        let (previous_expiry, term_secs) = check_renewal(&ctx.accounts.licence, &ctx.accounts.licence_template)?;
+
+        let purchase_price = validate_purchase(
+            &ctx.accounts.licence.key(),
+            &ctx.accounts.certificate_details,
+            &ctx.accounts.licence_template,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.payment_mint,
+            max_price,
+            false,
+        )?;
+        // Renewals pay out immediately, so they are unavailable while the platform requires escrow.
+        require!(
+            ctx.accounts.platform_config.escrow_period == 0,
+            CreatorClaimLicenceError::EscrowRequired
+        );
+
+        let (platform_fee, transfer_fee) = pay_fee_and_royalties(
+            &ctx.accounts.token_program.to_account_info(),
+            &ctx.accounts.holder_token_account.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.treasury_token_account.to_account_info(),
+            ctx.remaining_accounts,
+            &ctx.accounts.certificate_details.royalty_splits,
+            &ctx.accounts.payment_mint,
+            ctx.accounts.platform_config.fee_bps,
+            purchase_price,
+            true,
+            &[],
+        )?;
+
+        extend_licence(
+            &ctx.accounts.certificate_program,
+            &ctx.accounts.licence_authority,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.certificate_details,
+            &mut ctx.accounts.licence,
+            &ctx.accounts.licence_template,
+            previous_expiry,
+            term_secs,
+            ctx.bumps.licence_authority,
+            purchase_price,
+            platform_fee,
+            transfer_fee,
+        )
+    }
+
+    /// Instruction for a licence holder to renew a time-limited licence in native SOL,
+    /// for certificates that declare a `lamport_price`. Mirrors `renew_licence`: the
+    /// platform fee goes to the `PlatformConfig.treasury` wallet and the rest is paid to
+    /// each royalty beneficiary's wallet.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     max_price: The most the holder will pay in lamports; the certificate's current
+    ///                `lamport_price` is charged.
+    ///
+    /// Accounts:
+    ///     holder: The current licence holder (signer, pays in SOL).
+    ///     licence: The Licence PDA to renew.
+    ///     certificate_details: The CertificateDetails account of the licence.
+    ///     licence_template: The LicenceTemplate the licence was issued under.
+    ///     system_program: System program.
+    ///     licence_authority: PDA signing the certificate program's licence-count hook.
+    ///     certificate_program: The CreatorClaim certificate program.
+    ///     platform_config: The certificate program's PlatformConfig PDA (fee, treasury).
+    ///     treasury: The `PlatformConfig.treasury` wallet receiving the platform fee.
+    ///     remaining_accounts: One writable wallet per royalty split, in the same order as
+    ///                         `royalty_splits`, each equal to that split's beneficiary.
+    pub fn renew_licence_with_sol<'info>(
+        ctx: Context<'_, '_, '_, 'info, RenewLicenceWithSol<'info>>,
+        max_price: u64,
+    ) -> Result<()> {
+        let (previous_expiry, term_secs) = check_renewal(&ctx.accounts.licence, &ctx.accounts.licence_template)?;
+
+        let certificate_details = &ctx.accounts.certificate_details;
+        validate_listing(&ctx.accounts.licence.key(), certificate_details, &ctx.accounts.licence_template)?;
+        let purchase_price = certificate_details
+            .lamport_price
+            .ok_or(CreatorClaimLicenceError::LamportsNotAccepted)?;
+        require!(purchase_price <= max_price, CreatorClaimLicenceError::SlippageExceeded);
+        require!(
+            ctx.accounts.platform_config.escrow_period == 0,
+            CreatorClaimLicenceError::EscrowRequired
+        );
+
+        let platform_fee = pay_fee_and_royalties_lamports(
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.holder.to_account_info(),
+            &ctx.accounts.treasury.to_account_info(),
+            ctx.remaining_accounts,
+            &certificate_details.royalty_splits,
+            ctx.accounts.platform_config.fee_bps,
+            purchase_price,
+        )?;
+
+        extend_licence(
+            &ctx.accounts.certificate_program,
+            &ctx.accounts.licence_authority,
+            &ctx.accounts.platform_config,
+            &ctx.accounts.certificate_details,
+            &mut ctx.accounts.licence,
+            &ctx.accounts.licence_template,
+            previous_expiry,
+            term_secs,
+            ctx.bumps.licence_authority,
+            purchase_price,
+            platform_fee,
+            0,
+        )
+    }
+
+    /// Instruction for the current holder to hand a licence to another wallet,
+    /// e.g. an agency passing a licence on to its end customer. Only allowed when the
+    /// licence's template is transferable. If the template sets a resale royalty, the
+    /// holder pays `resale_royalty_bps` of the purchase price to the royalty splits.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     new_holder: The wallet receiving the licence.
+    ///
+    /// Accounts:
+    ///     holder: The current licence holder (signer).
+    ///     licence: The Licence PDA to transfer.
+    ///     certificate_details: The CertificateDetails account of the licence (royalty splits).
+    ///     licence_template: The LicenceTemplate the licence was issued under.
+    ///     holder_token_account: Optional. The holder's token account paying the resale royalty,
+    ///                           in the licence's `payment_mint`.
+    ///     payment_mint: Optional. The licence's payment mint, required for a resale royalty.
+    ///     token_program: Optional. The token program owning `payment_mint`, required for a resale royalty.
+    ///     remaining_accounts: When a resale royalty is due, one token account per royalty
+    ///                         split, in the same order as `royalty_splits`, each owned by
+    ///                         that split's beneficiary.
+    pub fn transfer_licence<'info>(
+        ctx: Context<'_, '_, '_, 'info, TransferLicence<'info>>,
+        new_holder: Pubkey,
+    ) -> Result<()> {
+        let licence = &ctx.accounts.licence;
+        require!(!licence.is_sublicence(), CreatorClaimLicenceError::IsSublicence);
+        require!(ctx.accounts.licence_template.transferable, CreatorClaimLicenceError::NotTransferable);
+        require!(licence.status != LicenceStatus::Revoked, CreatorClaimLicenceError::LicenceRevoked);
+        require!(licence.status != LicenceStatus::Expired, CreatorClaimLicenceError::LicenceExpired);
+        // Escrow refunds go back to the original buyer, so the licence stays put until released.
+        require!(licence.escrow_release_at.is_none(), CreatorClaimLicenceError::EscrowPending);
+
+        let resale_royalty = bps_of(licence.purchase_price, ctx.accounts.licence_template.resale_royalty_bps)?;
+        if resale_royalty > 0 {
+            let (Some(holder_token_account), Some(payment_mint), Some(token_program)) = (
+                &ctx.accounts.holder_token_account,
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.token_program,
+            ) else {
+                return err!(CreatorClaimLicenceError::MissingPaymentAccounts);
+            };
+            // The royalty is a share of `purchase_price`, so it is paid in the same mint
+            require_keys_eq!(
+                holder_token_account.mint,
+                licence.payment_mint,
+                CreatorClaimLicenceError::PaymentMintMismatch
+            );
+
+            distribute_royalties(
+                &token_program.to_account_info(),
+                &holder_token_account.to_account_info(),
+                &ctx.accounts.holder.to_account_info(),
+                ctx.remaining_accounts,
+                &ctx.accounts.certificate_details.royalty_splits,
+                payment_mint,
+                resale_royalty,
+                true,
+                &[],
+            )?;
+            msg!("Resale royalty of {} paid to royalty splits", resale_royalty);
+        }
+
+        let licence = &mut ctx.accounts.licence;
+        let previous_holder = licence.holder;
+        licence.holder = new_holder;
+
+        msg!("Licence {} transferred from {} to {}", licence.key(), previous_holder, new_holder);
+        emit!(LicenceTransferred {
+            licence_pda: licence.key(),
+            certificate_details: licence.certificate_details,
+            previous_holder,
+            new_holder,
+            resale_royalty,
         });
+        Ok(())
+    }
+
+    /// Instruction for the holder of an active, sublicensable licence to grant a
+    /// sublicence to another wallet. The sublicence points to its parent, ends when
+    /// the parent is due to expire, and lapses early if the parent is revoked
+    /// (see `lapse_sublicence`). Sublicences are not counted in the certificate's
+    /// active licences and cannot themselves be sublicensed, renewed or transferred.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     sublicensee: The wallet receiving the sublicence.
+    ///
+    /// Accounts:
+    ///     holder: The parent licence's holder (signer, pays rent). Recorded as the sublicence's
+    ///             `buyer`, so `expire_licence` returns the rent to it.
+    ///     parent_licence: The sublicensable parent Licence PDA.
+    ///     certificate_details: The CertificateDetails account of the parent; must be Active.
+    ///     licence_template: The LicenceTemplate the parent was issued under.
+    ///     sublicence: The child Licence PDA to be initialized. Seeds: ["sublicence", parent_licence, sublicensee]
+    ///     system_program: System program.
+    pub fn issue_sublicence(ctx: Context<IssueSublicence>, sublicensee: Pubkey) -> Result<()> {
+        let parent = &ctx.accounts.parent_licence;
+        require!(!parent.is_sublicence(), CreatorClaimLicenceError::IsSublicence);
+        require!(ctx.accounts.licence_template.sublicensable, CreatorClaimLicenceError::NotSublicensable);
+        require!(parent.status != LicenceStatus::Revoked, CreatorClaimLicenceError::LicenceRevoked);
+        require!(parent.status != LicenceStatus::Expired, CreatorClaimLicenceError::LicenceExpired);
+        // No new rights may be granted on a delisted or taken-down work
+        let certificate_details = &ctx.accounts.certificate_details;
+        require!(
+            certificate_details.status != CertificateStatus::Retired,
+            CreatorClaimLicenceError::CertificateRetired
+        );
+        require!(
+            certificate_details.status != CertificateStatus::Suspended,
+            CreatorClaimLicenceError::CertificateSuspended
+        );
+
+        // A parent still marked Active may be past its expiry if nobody has cranked it yet
+        let now = Clock::get()?.unix_timestamp;
+        if let Some(expiry_timestamp) = parent.expiry_timestamp {
+            require!(now <= expiry_timestamp, CreatorClaimLicenceError::LicenceExpired);
+        }
+
+        let sublicence = &mut ctx.accounts.sublicence;
+        sublicence.certificate_details = parent.certificate_details;
+        // The issuer paid the account's rent, so it is the one refunded when the sublicence is closed
+        sublicence.buyer = ctx.accounts.holder.key();
+        sublicence.holder = sublicensee;
+        sublicence.purchase_price = 0;
+        sublicence.payment_mint = parent.payment_mint;
+        sublicence.transfer_fee_paid = 0;
+        sublicence.purchase_timestamp = now;
+        sublicence.expiry_timestamp = parent.expiry_timestamp;
+        sublicence.status = LicenceStatus::Active;
+        sublicence.escrow_release_at = None;
+        sublicence.renewal_count = 0;
+        sublicence.licence_template_id = parent.licence_template_id;
+        sublicence.parent = Some(parent.key());
+        sublicence.bump = ctx.bumps.sublicence;
+
+        msg!("Sublicence {} issued from {} to {}", sublicence.key(), parent.key(), sublicensee);
+        emit!(SublicenceIssued {
+            sublicence_pda: sublicence.key(),
+            parent_licence: parent.key(),
+            certificate_details: sublicence.certificate_details,
+            sublicensee,
+            expiry_timestamp: sublicence.expiry_timestamp,
+        });
+        Ok(())
+    }
+
+    /// Permissionless crank invalidating a sublicence whose parent is no longer active.
+    /// The sublicence takes the parent's status (Revoked or Expired); a parent that
+    /// has been closed counts as Expired. Until this runs the sublicence still reads
+    /// Active, so consumers must not trust its status alone: load the `parent` licence
+    /// and use `Licence::is_in_force`.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///
+    /// Accounts:
+    ///     sublicence: The active sublicence to lapse.
+    ///     parent_licence: The sublicence's parent Licence PDA, possibly already closed.
+    pub fn lapse_sublicence(ctx: Context<LapseSublicence>) -> Result<()> {
+        let parent_info = &ctx.accounts.parent_licence;
+        let now = Clock::get()?.unix_timestamp;
+
+        let new_status = if parent_info.owner != &crate::ID || parent_info.data_is_empty() {
+            LicenceStatus::Expired
+        } else {
+            let data = parent_info.try_borrow_data()?;
+            let parent = Licence::try_deserialize(&mut &data[..])?;
+            parent.ended_status(now).ok_or(CreatorClaimLicenceError::ParentLicenceActive)?
+        };
+
+        let sublicence = &mut ctx.accounts.sublicence;
+        sublicence.status = new_status.clone();
+
+        msg!("Sublicence {} lapsed with parent {}: {:?}", sublicence.key(), parent_info.key(), new_status);
+        emit!(SublicenceLapsed {
+            sublicence_pda: sublicence.key(),
+            parent_licence: parent_info.key(),
+            status: new_status,
+        });
+        Ok(())
+    }
+
+    /// Instruction for a platform admin to define a licence template (PRD 12.1).
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     template_id: The ID certificates reference in `licence_template_id`.
+    ///     params: Term, exclusivity, sublicensable flag, region, transfer rules and active flag.
+    ///
+    /// Accounts:
+    ///     admin: One of the platform admins (signer, pays rent).
+    ///     platform_config: The certificate program's PlatformConfig PDA (admin set).
+    ///     licence_template: The LicenceTemplate PDA to be initialized. Seeds: ["licence_template", template_id]
+    ///     system_program: System program.
+    pub fn create_licence_template(
+        ctx: Context<CreateLicenceTemplate>,
+        template_id: u16,
+        params: LicenceTemplateParams,
+    ) -> Result<()> {
+        let licence_template = &mut ctx.accounts.licence_template;
+        licence_template.template_id = template_id;
+        licence_template.apply(params)?;
+        licence_template.bump = ctx.bumps.licence_template;
+
+        msg!("Licence template {} created", template_id);
+        emit_template_updated(licence_template);
+        Ok(())
+    }
+
+    /// Instruction for a platform admin to change or deactivate a licence template.
+    /// Existing licences keep the expiry they were issued with.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///     params: The new term, exclusivity, sublicensable flag, region, transfer rules and active flag.
+    ///
+    /// Accounts:
+    ///     admin: One of the platform admins (signer).
+    ///     platform_config: The certificate program's PlatformConfig PDA (admin set).
+    ///     licence_template: The LicenceTemplate PDA.
+    pub fn update_licence_template(
+        ctx: Context<UpdateLicenceTemplate>,
+        params: LicenceTemplateParams,
+    ) -> Result<()> {
+        let licence_template = &mut ctx.accounts.licence_template;
+        licence_template.apply(params)?;
+
+        msg!("Licence template {} updated", licence_template.template_id);
+        emit_template_updated(licence_template);
+        Ok(())
+    }
+
+    /// Instruction to create the Treasury token account for a payment mint.
+    /// The Treasury is its own token authority, so only this program can move its funds.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///
+    /// Accounts:
+    ///     admin: One of the platform admins (signer, pays rent).
+    ///     platform_config: The certificate program's PlatformConfig PDA (admin set).
+    ///     payment_mint: The mint the Treasury will hold fees in.
+    ///     treasury_token_account: The Treasury PDA to be initialized. Seeds: ["treasury", payment_mint]
+    ///     token_program: The token program owning `payment_mint`.
+    ///     system_program: System program.
+    pub fn initialize_treasury(ctx: Context<InitializeTreasury>) -> Result<()> {
+        msg!(
+            "Treasury {} initialized for mint {}",
+            ctx.accounts.treasury_token_account.key(),
+            ctx.accounts.payment_mint.key()
+        );
+        Ok(())
+    }
+
+    /// Instruction to sweep the accumulated platform fees for a payment mint
+    /// to a token account owned by the platform's treasury wallet.
+    ///
+    /// Args:
+    ///     ctx: Context containing accounts needed.
+    ///
+    /// Accounts:
+    ///     admin: One of the platform admins (signer).
+    ///     platform_config: The certificate program's PlatformConfig PDA (admin set, treasury wallet).
+    ///     payment_mint: The mint the fees are held in.
+    ///     treasury_token_account: The Treasury PDA holding the fees.
+    ///     destination_token_account: Token account owned by `platform_config.treasury`.
+    ///     token_program: The token program owning `payment_mint`.
+    pub fn withdraw_fees(ctx: Context<WithdrawFees>) -> Result<()> {
+        let treasury_token_account = &ctx.accounts.treasury_token_account;
+        let amount = treasury_token_account.amount;
+        require!(amount > 0, CreatorClaimLicenceError::ZeroBalance);
+
+        let payment_mint_key = ctx.accounts.payment_mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"treasury",
+            payment_mint_key.as_ref(),
+            &[ctx.bumps.treasury_token_account],
+        ]];
+        transfer_tokens(
+            &ctx.accounts.token_program.to_account_info(),
+            &treasury_token_account.to_account_info(),
+            &ctx.accounts.payment_mint,
+            &ctx.accounts.destination_token_account.to_account_info(),
+            &treasury_token_account.to_account_info(),
+            amount,
+            signer_seeds,
+        )?;
 
+        msg!("Withdrew {} in platform fees to {}", amount, ctx.accounts.destination_token_account.key());
+        emit!(FeesWithdrawn {
+            payment_mint: payment_mint_key,
+            destination: ctx.accounts.destination_token_account.key(),
+            admin: ctx.accounts.admin.key(),
+            amount,
+        });
         Ok(())
     }
 }
 
-/// Context for the `purchase_licence` instruction (Updated for Token-2022).
+/// Context for the `purchase_licence` instruction.
 #[derive(Accounts)]
-#[instruction(purchase_price: u64, expiry_timestamp: Option<i64>)]
 pub struct PurchaseLicence<'info> {
     #[account(mut)]
     pub buyer: Signer<'info>,
 
     #[account(mut,
-        token::program = token_program, // Use Token-2022 program ID
-        token::mint = payment_mint
+        token::mint = payment_mint,
+        token::token_program = token_program
     )]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
 
     /// Initialize the Licence PDA.
     /// Seeds ensure uniqueness per certificate per buyer.
@@ -222,61 +1153,919 @@ pub struct PurchaseLicence<'info> {
         payer = buyer,
         space = Licence::LEN,
         seeds = [b"licence", certificate_details.key().as_ref(), buyer.key().as_ref()],
-        bump,
-        owner = system_program.key()
+        bump
     )]
     pub licence: Account<'info, Licence>,
 
     /// Certificate Details account. Need its data to validate price.
+    /// Mutable because the certificate program updates its active licence count.
     #[account(
-        owner = CERTIFICATE_PROGRAM_ID.parse::<Pubkey>().expect("Invalid Certificate Program ID constant")
+        mut,
+        owner = creatorclaim_certificate::ID
     )]
     pub certificate_details: Account<'info, CertificateDetails>,
 
-    /// The Token-2022 Mint configured with the Transfer Fee extension.
-    #[account(token::program = token_program)] // Check ownership by Token-2022
-    pub payment_mint: Account<'info, Mint>,
+    /// The certificate's licence template.
+    #[account(
+        seeds = [b"licence_template", certificate_details.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    /// The payment mint, owned by either SPL Token or Token-2022.
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
 
-    /// The Token-2022 Program itself.
-    #[account(address = TOKEN_2022_PROGRAM_ID)]
-    pub token_program: Program<'info, Token>,
+    /// The token program owning `payment_mint` (SPL Token or Token-2022).
+    pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 
-    // Royalty recipient accounts, treasury account, etc., must be passed
-    // in ctx.remaining_accounts in the order expected by the transfer hook.
-}
-
-/// Context for the `revoke_licence` instruction.
-#[derive(Accounts)]
-pub struct RevokeLicence<'info> {
-    /// The signer attempting to revoke the licence.
-    /// Must have authority (e.g., be the authority stored in CertificateDetails).
-    pub revoker: Signer<'info>,
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
 
-    /// The licence account to be modified.
-    #[account(mut,
-        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
     )]
-    pub licence: Account<'info, Licence>,
+    pub platform_config: Account<'info, PlatformConfig>,
 
-    /// The CertificateDetails account associated with the licence.
-    /// Used to verify if the `revoker` has the correct authority.
+    /// Treasury token account receiving the platform fee.
     #[account(
-        owner = CERTIFICATE_PROGRAM_ID.parse::<Pubkey>().expect("Invalid Certificate Program ID constant"),
-        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+        mut,
+        seeds = [b"treasury", payment_mint.key().as_ref()],
+        bump
     )]
-    // Load the account data to access the authority field.
-    pub certificate_details: Account<'info, CertificateDetails>,
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Royalty beneficiary token accounts must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
 }
 
-// --- Events ---
-#[event]
-pub struct LicencePurchased {
+/// Context for the `purchase_licence_with_sol` instruction.
+#[derive(Accounts)]
+pub struct PurchaseLicenceWithSol<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    /// Same seeds as `purchase_licence`, so a buyer holds one licence per work either way.
+    #[account(
+        init,
+        payer = buyer,
+        space = Licence::LEN,
+        seeds = [b"licence", certificate_details.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// Mutable because the certificate program updates its active licence count.
+    #[account(mut, owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The certificate's licence template.
+    #[account(
+        seeds = [b"licence_template", certificate_details.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// The platform treasury wallet receiving the fee in lamports.
+    #[account(mut, address = platform_config.treasury @ CreatorClaimLicenceError::InvalidRecipientAccount)]
+    pub treasury: SystemAccount<'info>,
+
+    // Royalty beneficiary wallets must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
+}
+
+/// Context for the `purchase_licence_escrowed` instruction.
+#[derive(Accounts)]
+pub struct PurchaseLicenceEscrowed<'info> {
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init,
+        payer = buyer,
+        space = Licence::LEN,
+        seeds = [b"licence", certificate_details.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// Escrow token account holding the payment, owned by itself.
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"escrow", licence.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = escrow_token_account
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mutable because the certificate program updates its active licence count.
+    #[account(mut, owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The certificate's licence template.
+    #[account(
+        seeds = [b"licence_template", certificate_details.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Context for the `release_escrow` instruction.
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(
+        mut,
+        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// CHECK: Only receives the escrow account's rent; must be the licence buyer.
+    #[account(mut, address = licence.buyer @ CreatorClaimLicenceError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// Mutable because the certificate program may update its active licence count.
+    #[account(mut, owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"escrow", licence.key().as_ref()],
+        bump,
+        token::mint = payment_mint
+    )]
+    pub escrow_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", payment_mint.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    // Royalty beneficiary token accounts must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
+}
+
+/// Context for the `revoke_licence` instruction.
+#[derive(Accounts)]
+pub struct RevokeLicence<'info> {
+    /// The signer attempting to revoke the licence.
+    /// Must have authority (e.g., be the authority stored in CertificateDetails).
+    /// Mutable because it funds SOL refunds.
+    #[account(mut)]
+    pub revoker: Signer<'info>,
+
+    /// The licence account to be modified.
+    #[account(mut,
+        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// The CertificateDetails account associated with the licence.
+    /// Used to verify if the `revoker` has the correct authority.
+    #[account(
+        mut,
+        owner = creatorclaim_certificate::ID,
+        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    )]
+    // Load the account data to access the authority field.
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Escrow holding the payment; required to refund inside the dispute window.
+    #[account(mut, seeds = [b"escrow", licence.key().as_ref()], bump)]
+    pub escrow_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The licence holder's token account receiving a refund. Licences cannot change
+    /// hands while escrowed, so for escrow refunds this is also the original buyer.
+    #[account(
+        mut,
+        constraint = buyer_token_account.owner == licence.holder @ CreatorClaimLicenceError::HolderMismatch
+    )]
+    pub buyer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Only receives the escrow account's rent; must be the licence buyer.
+    #[account(mut, address = licence.buyer @ CreatorClaimLicenceError::BuyerMismatch)]
+    pub buyer: Option<UncheckedAccount<'info>>,
+
+    /// The revoker's token account funding a `refund_bps` refund.
+    #[account(
+        mut,
+        constraint = revoker_token_account.owner == revoker.key() @ CreatorClaimLicenceError::InvalidRecipientAccount
+    )]
+    pub revoker_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = licence.payment_mint @ CreatorClaimLicenceError::PaymentMintMismatch)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// CHECK: Only receives a SOL refund; must be the licence holder.
+    #[account(mut, address = licence.holder @ CreatorClaimLicenceError::HolderMismatch)]
+    pub holder: Option<UncheckedAccount<'info>>,
+
+    pub system_program: Option<Program<'info, System>>,
+}
+
+/// Context for the `expire_licence` instruction.
+#[derive(Accounts)]
+pub struct ExpireLicence<'info> {
+    #[account(mut)]
+    pub licence: Account<'info, Licence>,
+
+    /// CHECK: Only receives the licence account's rent; must be the licence buyer.
+    #[account(mut, address = licence.buyer @ CreatorClaimLicenceError::BuyerMismatch)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: The licence's CertificateDetails, which `retire_certificate` may already have closed.
+    /// Only passed to the certificate program's hook, which validates it, while it is still open.
+    /// Mutable because the certificate program updates its active licence count.
+    #[account(mut, address = licence.certificate_details @ CreatorClaimLicenceError::CertificateMismatch)]
+    pub certificate_details: UncheckedAccount<'info>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+/// Context for the `renew_licence` instruction.
+#[derive(Accounts)]
+pub struct RenewLicence<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(mut, token::mint = payment_mint)]
+    pub holder_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [b"licence", certificate_details.key().as_ref(), licence.buyer.as_ref()],
+        bump = licence.bump,
+        constraint = licence.holder == holder.key() @ CreatorClaimLicenceError::HolderMismatch
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// Mutable because the certificate program updates its active licence count.
+    #[account(mut, owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The licence template the licence was issued under.
+    #[account(
+        seeds = [b"licence_template", licence.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Treasury token account receiving the platform fee.
+    #[account(
+        mut,
+        seeds = [b"treasury", payment_mint.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    // Royalty beneficiary token accounts must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
+}
+
+/// Context for the `renew_licence_with_sol` instruction.
+#[derive(Accounts)]
+pub struct RenewLicenceWithSol<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"licence", certificate_details.key().as_ref(), licence.buyer.as_ref()],
+        bump = licence.bump,
+        constraint = licence.holder == holder.key() @ CreatorClaimLicenceError::HolderMismatch
+    )]
+    pub licence: Account<'info, Licence>,
+
+    /// Mutable because the certificate program updates its active licence count.
+    #[account(mut, owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The licence template the licence was issued under.
+    #[account(
+        seeds = [b"licence_template", licence.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: PDA that signs the certificate program's licence-count hooks. Holds no data.
+    #[account(seeds = [b"licence_authority"], bump)]
+    pub licence_authority: UncheckedAccount<'info>,
+    pub certificate_program: Program<'info, CreatorclaimCertificate>,
+
+    /// Platform-wide settings owned by the certificate program.
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// The platform treasury wallet receiving the platform fee.
+    #[account(mut, address = platform_config.treasury @ CreatorClaimLicenceError::InvalidRecipientAccount)]
+    pub treasury: SystemAccount<'info>,
+
+    // Royalty beneficiary wallets must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
+}
+
+/// Context for the `transfer_licence` instruction.
+#[derive(Accounts)]
+pub struct TransferLicence<'info> {
+    pub holder: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = licence.holder == holder.key() @ CreatorClaimLicenceError::HolderMismatch,
+        constraint = licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    )]
+    pub licence: Account<'info, Licence>,
+
+    #[account(owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The licence template the licence was issued under.
+    #[account(
+        seeds = [b"licence_template", licence.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    #[account(
+        mut,
+        constraint = holder_token_account.owner == holder.key() @ CreatorClaimLicenceError::HolderMismatch
+    )]
+    pub holder_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = licence.payment_mint @ CreatorClaimLicenceError::PaymentMintMismatch)]
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    // Royalty beneficiary token accounts must be passed in ctx.remaining_accounts,
+    // in the same order as the certificate's `royalty_splits`.
+}
+
+/// Context for the `issue_sublicence` instruction.
+#[derive(Accounts)]
+#[instruction(sublicensee: Pubkey)]
+pub struct IssueSublicence<'info> {
+    #[account(mut)]
+    pub holder: Signer<'info>,
+
+    #[account(
+        constraint = parent_licence.holder == holder.key() @ CreatorClaimLicenceError::HolderMismatch,
+        constraint = parent_licence.certificate_details == certificate_details.key() @ CreatorClaimLicenceError::CertificateMismatch
+    )]
+    pub parent_licence: Account<'info, Licence>,
+
+    #[account(owner = creatorclaim_certificate::ID)]
+    pub certificate_details: Account<'info, CertificateDetails>,
+
+    /// The licence template the parent was issued under.
+    #[account(
+        seeds = [b"licence_template", parent_licence.licence_template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    #[account(
+        init,
+        payer = holder,
+        space = Licence::LEN,
+        seeds = [b"sublicence", parent_licence.key().as_ref(), sublicensee.as_ref()],
+        bump
+    )]
+    pub sublicence: Account<'info, Licence>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the `lapse_sublicence` instruction.
+#[derive(Accounts)]
+pub struct LapseSublicence<'info> {
+    #[account(
+        mut,
+        constraint = sublicence.parent == Some(parent_licence.key()) @ CreatorClaimLicenceError::ParentMismatch,
+        constraint = sublicence.status == LicenceStatus::Active @ CreatorClaimLicenceError::LicenceRevoked
+    )]
+    pub sublicence: Account<'info, Licence>,
+
+    /// CHECK: The sublicence's parent. May already be closed, so it is deserialized by hand.
+    pub parent_licence: UncheckedAccount<'info>,
+}
+
+/// Context for the `create_licence_template` instruction.
+#[derive(Accounts)]
+#[instruction(template_id: u16)]
+pub struct CreateLicenceTemplate<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimLicenceError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = LicenceTemplate::LEN,
+        seeds = [b"licence_template", template_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the `update_licence_template` instruction.
+#[derive(Accounts)]
+pub struct UpdateLicenceTemplate<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimLicenceError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"licence_template", licence_template.template_id.to_le_bytes().as_ref()],
+        bump = licence_template.bump
+    )]
+    pub licence_template: Account<'info, LicenceTemplate>,
+}
+
+/// Context for the `initialize_treasury` instruction.
+#[derive(Accounts)]
+pub struct InitializeTreasury<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimLicenceError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// The Treasury token account, owned by itself.
+    #[account(
+        init,
+        payer = admin,
+        seeds = [b"treasury", payment_mint.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = treasury_token_account
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Context for the `withdraw_fees` instruction.
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"platform_config"],
+        bump = platform_config.bump,
+        seeds::program = creatorclaim_certificate::ID,
+        constraint = platform_config.is_admin(&admin.key()) @ CreatorClaimLicenceError::UnauthorizedAdmin
+    )]
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", payment_mint.key().as_ref()],
+        bump
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Fees can only leave to the platform's treasury wallet.
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        constraint = destination_token_account.owner == platform_config.treasury @ CreatorClaimLicenceError::InvalidRecipientAccount
+    )]
+    pub destination_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// --- Purchase checks ---
+
+/// Checks shared by every token purchase path: the certificate is sellable under an
+/// active template and not exclusively licensed to another licence, the payment mint is
+/// accepted by the platform, and what the buyer is debited is within their `max_price`.
+/// That total includes any Token-2022 transfer fees the buyer covers: one per payout leg,
+/// or a single one on the deposit when `escrowed`.
+/// Returns the price to charge, which is always the certificate's current price.
+fn validate_purchase(
+    licence: &Pubkey,
+    certificate_details: &Account<CertificateDetails>,
+    licence_template: &LicenceTemplate,
+    platform_config: &PlatformConfig,
+    payment_mint: &InterfaceAccount<Mint>,
+    max_price: u64,
+    escrowed: bool,
+) -> Result<u64> {
+    require!(
+        platform_config.accepted_payment_mints.contains(&payment_mint.key()),
+        CreatorClaimLicenceError::PaymentMintNotAccepted
+    );
+    require_keys_eq!(
+        payment_mint.key(),
+        certificate_details.payment_mint,
+        CreatorClaimLicenceError::PaymentMintMismatch
+    );
+    validate_listing(licence, certificate_details, licence_template)?;
+
+    // Charge the listed price, so a stale quote can never overpay; a reprice (or a
+    // transfer fee change) above what the buyer signed for fails instead.
+    let price = certificate_details.price;
+    let total = if escrowed {
+        transfer_total(payment_mint, price)?
+    } else {
+        payment_total(payment_mint, price, platform_config.fee_bps, &certificate_details.royalty_splits)?
+    };
+    msg!("Listed price: {}, total with transfer fees: {}, max price: {}", price, total, max_price);
+    require!(total <= max_price, CreatorClaimLicenceError::SlippageExceeded);
+
+    Ok(price)
+}
+
+/// Checks that the certificate is open for new licences under `licence_template`,
+/// whatever currency the buyer pays in.
+fn validate_listing(
+    licence: &Pubkey,
+    certificate_details: &Account<CertificateDetails>,
+    licence_template: &LicenceTemplate,
+) -> Result<()> {
+    // Ensure the provided certificate_details account is owned by the correct program
+    require_keys_eq!(
+        *certificate_details.to_account_info().owner,
+        creatorclaim_certificate::ID,
+        CreatorClaimLicenceError::CertificateMismatch
+    );
+    require!(
+        certificate_details.status != CertificateStatus::Retired,
+        CreatorClaimLicenceError::CertificateRetired
+    );
+    require!(
+        certificate_details.status != CertificateStatus::Suspended,
+        CreatorClaimLicenceError::CertificateSuspended
+    );
+    require!(
+        licence_template.active && licence_template.template_id == certificate_details.licence_template_id,
+        CreatorClaimLicenceError::ExpiredTemplate
+    );
+    if let Some(exclusive_licence) = certificate_details.active_exclusive_licence(Clock::get()?.unix_timestamp) {
+        require_keys_eq!(exclusive_licence, *licence, CreatorClaimLicenceError::ExclusiveLicenceActive);
+    }
+
+    Ok(())
+}
+
+/// Checks that `licence` can be renewed under `licence_template`. Returns its current
+/// expiry and the template term to extend it by.
+fn check_renewal(licence: &Licence, licence_template: &LicenceTemplate) -> Result<(i64, i64)> {
+    require!(!licence.is_sublicence(), CreatorClaimLicenceError::IsSublicence);
+    require!(licence.status != LicenceStatus::Revoked, CreatorClaimLicenceError::LicenceRevoked);
+    let previous_expiry = licence.expiry_timestamp.ok_or(CreatorClaimLicenceError::PerpetualLicence)?;
+    require!(
+        licence.licence_template_id == licence_template.template_id,
+        CreatorClaimLicenceError::ExpiredTemplate
+    );
+    let term_secs = licence_template.term_secs.ok_or(CreatorClaimLicenceError::PerpetualLicence)?;
+    Ok((previous_expiry, term_secs))
+}
+
+/// Applies a paid renewal: pushes the expiry forward by `term_secs` from the later of now
+/// and `previous_expiry`, retakes the exclusive lock and the active licence slot as needed,
+/// and reactivates a licence already marked Expired.
+#[allow(clippy::too_many_arguments)]
+fn extend_licence<'info>(
+    certificate_program: &Program<'info, CreatorclaimCertificate>,
+    licence_authority: &UncheckedAccount<'info>,
+    platform_config: &Account<'info, PlatformConfig>,
+    certificate_details: &Account<'info, CertificateDetails>,
+    licence: &mut Account<'info, Licence>,
+    licence_template: &LicenceTemplate,
+    previous_expiry: i64,
+    term_secs: i64,
+    licence_authority_bump: u8,
+    purchase_price: u64,
+    platform_fee: u64,
+    transfer_fee: u64,
+) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let new_expiry = previous_expiry
+        .max(now)
+        .checked_add(term_secs)
+        .ok_or(CreatorClaimLicenceError::InvalidExtension)?;
+
+    // Extend (or, after expiry, retake) the exclusive lock before reactivating
+    if licence_template.exclusive {
+        record_exclusive_licence(
+            certificate_program,
+            licence_authority,
+            platform_config,
+            certificate_details,
+            licence.key(),
+            Some(new_expiry),
+            licence_authority_bump,
+        )?;
+    }
+
+    // An expired licence whose escrow is still pending never gave up its slot
+    let reactivated = licence.status == LicenceStatus::Expired;
+    if reactivated && !licence.counts_as_active() {
+        record_licence_activated(
+            certificate_program,
+            licence_authority,
+            platform_config,
+            certificate_details,
+            licence.key(),
+            licence_authority_bump,
+        )?;
+    }
+
+    licence.expiry_timestamp = Some(new_expiry);
+    licence.status = LicenceStatus::Active;
+    licence.renewal_count = licence
+        .renewal_count
+        .checked_add(1)
+        .ok_or(CreatorClaimLicenceError::InvalidExtension)?;
+
+    msg!("Licence {} renewed until {} (renewal #{})", licence.key(), new_expiry, licence.renewal_count);
+    emit!(LicenceRenewed {
+        licence_pda: licence.key(),
+        certificate_details: licence.certificate_details,
+        holder: licence.holder,
+        purchase_price,
+        platform_fee,
+        transfer_fee,
+        previous_expiry_timestamp: previous_expiry,
+        expiry_timestamp: new_expiry,
+        renewal_count: licence.renewal_count,
+        reactivated,
+    });
+    Ok(())
+}
+
+fn emit_template_updated(licence_template: &LicenceTemplate) {
+    emit!(LicenceTemplateUpdated {
+        template_id: licence_template.template_id,
+        term_secs: licence_template.term_secs,
+        exclusive: licence_template.exclusive,
+        sublicensable: licence_template.sublicensable,
+        region: licence_template.region,
+        transferable: licence_template.transferable,
+        resale_royalty_bps: licence_template.resale_royalty_bps,
+        active: licence_template.active,
+    });
+}
+
+// --- Certificate program hooks ---
+// The certificate program only accepts these calls when signed by our `licence_authority` PDA.
+
+/// Runs the certificate hooks for a newly purchased licence: exclusive templates lock
+/// the work for the licence term, then the licence is added to the active licence count.
+fn record_new_licence<'info>(
+    certificate_program: &Program<'info, CreatorclaimCertificate>,
+    licence_authority: &UncheckedAccount<'info>,
+    platform_config: &Account<'info, PlatformConfig>,
+    certificate_details: &Account<'info, CertificateDetails>,
+    licence: &Account<'info, Licence>,
+    exclusive: bool,
+    licence_authority_bump: u8,
+) -> Result<()> {
+    if exclusive {
+        record_exclusive_licence(
+            certificate_program,
+            licence_authority,
+            platform_config,
+            certificate_details,
+            licence.key(),
+            licence.expiry_timestamp,
+            licence_authority_bump,
+        )?;
+    }
+    record_licence_activated(
+        certificate_program,
+        licence_authority,
+        platform_config,
+        certificate_details,
+        licence.key(),
+        licence_authority_bump,
+    )
+}
+
+fn record_licence_activated<'info>(
+    certificate_program: &Program<'info, CreatorclaimCertificate>,
+    licence_authority: &UncheckedAccount<'info>,
+    platform_config: &Account<'info, PlatformConfig>,
+    certificate_details: &Account<'info, CertificateDetails>,
+    licence: Pubkey,
+    licence_authority_bump: u8,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"licence_authority", &[licence_authority_bump]]];
+    creatorclaim_certificate::cpi::record_licence_activated(
+        CpiContext::new_with_signer(
+            certificate_program.to_account_info(),
+            creatorclaim_certificate::cpi::accounts::LicenceHook {
+                licence_authority: licence_authority.to_account_info(),
+                platform_config: platform_config.to_account_info(),
+                certificate_details: certificate_details.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        licence,
+    )
+}
+
+fn record_licence_released<'info>(
+    certificate_program: &Program<'info, CreatorclaimCertificate>,
+    licence_authority: &UncheckedAccount<'info>,
+    platform_config: &Account<'info, PlatformConfig>,
+    certificate_details: &impl ToAccountInfo<'info>,
+    licence: Pubkey,
+    licence_authority_bump: u8,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"licence_authority", &[licence_authority_bump]]];
+    creatorclaim_certificate::cpi::record_licence_released(
+        CpiContext::new_with_signer(
+            certificate_program.to_account_info(),
+            creatorclaim_certificate::cpi::accounts::LicenceHook {
+                licence_authority: licence_authority.to_account_info(),
+                platform_config: platform_config.to_account_info(),
+                certificate_details: certificate_details.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        licence,
+    )
+}
+
+fn record_exclusive_licence<'info>(
+    certificate_program: &Program<'info, CreatorclaimCertificate>,
+    licence_authority: &UncheckedAccount<'info>,
+    platform_config: &Account<'info, PlatformConfig>,
+    certificate_details: &Account<'info, CertificateDetails>,
+    licence: Pubkey,
+    exclusive_until: Option<i64>,
+    licence_authority_bump: u8,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"licence_authority", &[licence_authority_bump]]];
+    creatorclaim_certificate::cpi::record_exclusive_licence(
+        CpiContext::new_with_signer(
+            certificate_program.to_account_info(),
+            creatorclaim_certificate::cpi::accounts::LicenceHook {
+                licence_authority: licence_authority.to_account_info(),
+                platform_config: platform_config.to_account_info(),
+                certificate_details: certificate_details.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        licence,
+        exclusive_until,
+    )
+}
+
+// --- Events ---
+#[event]
+pub struct LicencePurchased {
     pub licence_pda: Pubkey,
     pub certificate_details: Pubkey,
     pub buyer: Pubkey,
-    pub purchase_price: u64,
+    pub purchase_price: u64, // The amount charged: the listed price at purchase time
+    pub max_price: u64,
+    pub payment_mint: Pubkey,
+    pub platform_fee: u64,
+    pub transfer_fee: u64,
     pub purchase_timestamp: i64,
+    pub expiry_timestamp: Option<i64>,
+    pub licence_template_id: u16,
+    pub escrow_release_at: Option<i64>,
 }
 
 #[event]
@@ -284,18 +2073,96 @@ pub struct LicenceRevoked {
     pub licence_pda: Pubkey,
     pub certificate_details: Pubkey,
     pub revoker: Pubkey, // Who triggered the revoke (admin or creator)
+    pub refunded_amount: u64,
 }
 
-// Add new error for missing recipient account
-#[error_code]
-pub enum CreatorClaimLicenceError {
-    // ... (existing errors) ...
-    #[msg("Primary recipient account missing from remaining_accounts.")]
-    MissingRecipientAccount,
-    #[msg("Incorrect purchase price provided.")]
-    IncorrectPrice,
-    #[msg("Signer is not authorized to revoke this licence.")]
-    UnauthorizedRevoker,
-    #[msg("Invalid primary recipient account provided.")]
-    InvalidRecipientAccount,
-}
\ No newline at end of file
+#[event]
+pub struct LicenceExpired {
+    pub licence_pda: Pubkey,
+    pub certificate_details: Pubkey,
+    pub buyer: Pubkey,
+    pub expiry_timestamp: i64,
+    pub closed: bool,
+}
+
+#[event]
+pub struct LicenceClosed {
+    pub licence_pda: Pubkey,
+    pub certificate_details: Pubkey,
+    pub buyer: Pubkey,
+    pub status: LicenceStatus,
+}
+
+#[event]
+pub struct LicenceRenewed {
+    pub licence_pda: Pubkey,
+    pub certificate_details: Pubkey,
+    pub holder: Pubkey,
+    pub purchase_price: u64,
+    pub platform_fee: u64,
+    pub transfer_fee: u64,
+    pub previous_expiry_timestamp: i64,
+    pub expiry_timestamp: i64,
+    pub renewal_count: u32,
+    pub reactivated: bool,
+}
+
+#[event]
+pub struct LicenceTransferred {
+    pub licence_pda: Pubkey,
+    pub certificate_details: Pubkey,
+    pub previous_holder: Pubkey,
+    pub new_holder: Pubkey,
+    pub resale_royalty: u64,
+}
+
+#[event]
+pub struct SublicenceIssued {
+    pub sublicence_pda: Pubkey,
+    pub parent_licence: Pubkey,
+    pub certificate_details: Pubkey,
+    pub sublicensee: Pubkey,
+    pub expiry_timestamp: Option<i64>,
+}
+
+#[event]
+pub struct SublicenceLapsed {
+    pub sublicence_pda: Pubkey,
+    pub parent_licence: Pubkey,
+    pub status: LicenceStatus,
+}
+
+#[event]
+pub struct LicenceTemplateUpdated {
+    pub template_id: u16,
+    pub term_secs: Option<i64>,
+    pub exclusive: bool,
+    pub sublicensable: bool,
+    pub region: Option<[u8; 2]>,
+    pub transferable: bool,
+    pub resale_royalty_bps: u16,
+    pub active: bool,
+}
+
+#[event]
+pub struct FeesWithdrawn {
+    pub payment_mint: Pubkey,
+    pub destination: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct EscrowReleased {
+    pub licence_pda: Pubkey,
+    pub certificate_details: Pubkey,
+    pub amount: u64,
+    pub platform_fee: u64,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub licence_pda: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+}
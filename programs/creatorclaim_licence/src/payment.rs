@@ -0,0 +1,461 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token_interface::{self, Mint, TokenAccount};
+use spl_token_2022::extension::transfer_fee::TransferFeeConfig;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use spl_token_2022::state::Mint as MintState;
+
+use creatorclaim_certificate::state::RoyaltySplit;
+
+use crate::state::*;
+
+/// Returns `bps` basis points of `amount`, rounded down.
+pub fn bps_of(amount: u64, bps: u16) -> Result<u64> {
+    require!(bps <= 10_000, CreatorClaimLicenceError::RoyaltySplitError);
+    // Cannot overflow: bps <= 10_000 keeps the result at or below `amount`.
+    Ok(((amount as u128) * (bps as u128) / 10_000) as u64)
+}
+
+/// Splits `amount` into the platform fee (`fee_bps` of it, rounded down) and
+/// the remainder left for the royalty beneficiaries.
+pub fn split_fee(amount: u64, fee_bps: u16) -> Result<(u64, u64)> {
+    let fee = bps_of(amount, fee_bps)?;
+    Ok((fee, amount - fee))
+}
+
+/// Splits `amount` across `splits` by their bps share, rounding each share down.
+/// Whatever the rounding leaves over goes to the first beneficiary, so the
+/// shares always add up to exactly `amount`.
+pub fn split_amounts(amount: u64, splits: &[RoyaltySplit]) -> Result<Vec<u64>> {
+    require!(!splits.is_empty(), CreatorClaimLicenceError::RoyaltySplitError);
+
+    let mut shares = splits
+        .iter()
+        .map(|split| {
+            let share = (amount as u128) * (split.share_bps as u128) / 10_000;
+            u64::try_from(share).map_err(|_| error!(CreatorClaimLicenceError::RoyaltySplitError))
+        })
+        .collect::<Result<Vec<u64>>>()?;
+
+    let distributed = shares
+        .iter()
+        .try_fold(0u64, |acc, share| acc.checked_add(*share))
+        .ok_or(CreatorClaimLicenceError::RoyaltySplitError)?;
+    let remainder = amount
+        .checked_sub(distributed)
+        .ok_or(CreatorClaimLicenceError::RoyaltySplitError)?;
+    shares[0] = shares[0]
+        .checked_add(remainder)
+        .ok_or(CreatorClaimLicenceError::RoyaltySplitError)?;
+
+    Ok(shares)
+}
+
+/// Returns the extra tokens a sender must add to `net_amount` so that the recipient
+/// still receives `net_amount`, given the mint's TransferFee extension at `epoch`
+/// (capped at its `maximum_fee`). Mints without the extension charge nothing.
+pub fn inverse_transfer_fee(mint_data: &[u8], epoch: u64, net_amount: u64) -> Result<u64> {
+    // `payment_mint` may belong to either token program; a legacy mint unpacks as a
+    // Token-2022 mint without extensions and so pays no transfer fee.
+    let mint = StateWithExtensions::<MintState>::unpack(mint_data)
+        .map_err(|_| error!(CreatorClaimLicenceError::PaymentMintNotAccepted))?;
+    let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+    transfer_fee_config
+        .calculate_inverse_epoch_fee(epoch, net_amount)
+        .ok_or_else(|| error!(CreatorClaimLicenceError::TransferFeeOverflow))
+}
+
+/// Returns what the sender is debited in total when `amount` is paid out as the platform
+/// fee plus the royalty shares, each leg grossed up by its own transfer fee at `epoch`.
+/// Mirrors `pay_fee_and_royalties` with `gross_up`, including its skipped zero legs.
+pub fn grossed_up_total(
+    mint_data: &[u8],
+    epoch: u64,
+    amount: u64,
+    fee_bps: u16,
+    splits: &[RoyaltySplit],
+) -> Result<u64> {
+    let (platform_fee, royalty_amount) = split_fee(amount, fee_bps)?;
+    let mut legs = split_amounts(royalty_amount, splits)?;
+    legs.push(platform_fee);
+
+    legs.into_iter().filter(|leg| *leg > 0).try_fold(amount, |total, leg| {
+        let fee = inverse_transfer_fee(mint_data, epoch, leg)?;
+        total
+            .checked_add(fee)
+            .ok_or_else(|| error!(CreatorClaimLicenceError::TransferFeeOverflow))
+    })
+}
+
+/// Returns the Token-2022 transfer fee owed on top of `net_amount` in the current epoch.
+fn mint_transfer_fee(mint: &InterfaceAccount<Mint>, net_amount: u64) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(0);
+    }
+    let data = mint_info.try_borrow_data()?;
+    inverse_transfer_fee(&data, Clock::get()?.epoch, net_amount)
+}
+
+/// Returns the total a payer is debited for `pay_fee_and_royalties` with `gross_up`.
+pub fn payment_total(
+    mint: &InterfaceAccount<Mint>,
+    amount: u64,
+    fee_bps: u16,
+    splits: &[RoyaltySplit],
+) -> Result<u64> {
+    let mint_info = mint.to_account_info();
+    if *mint_info.owner != spl_token_2022::ID {
+        return Ok(amount);
+    }
+    let data = mint_info.try_borrow_data()?;
+    grossed_up_total(&data, Clock::get()?.epoch, amount, fee_bps, splits)
+}
+
+/// Returns the total a payer is debited for `transfer_tokens_net` of `net_amount`.
+pub fn transfer_total(mint: &InterfaceAccount<Mint>, net_amount: u64) -> Result<u64> {
+    net_amount
+        .checked_add(mint_transfer_fee(mint, net_amount)?)
+        .ok_or_else(|| error!(CreatorClaimLicenceError::TransferFeeOverflow))
+}
+
+/// Checks that `recipient` is a token account of `payment_mint`, held by the
+/// token program (SPL Token or Token-2022), whose owner is `split.beneficiary`.
+fn verify_recipient(
+    recipient: &AccountInfo,
+    split: &RoyaltySplit,
+    payment_mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<()> {
+    require_keys_eq!(*recipient.owner, *token_program, CreatorClaimLicenceError::SplitMismatch);
+
+    let data = recipient.try_borrow_data()?;
+    let token_account = TokenAccount::try_deserialize(&mut &data[..])
+        .map_err(|_| error!(CreatorClaimLicenceError::SplitMismatch))?;
+    require_keys_eq!(token_account.owner, split.beneficiary, CreatorClaimLicenceError::SplitMismatch);
+    require_keys_eq!(token_account.mint, *payment_mint, CreatorClaimLicenceError::SplitMismatch);
+
+    Ok(())
+}
+
+/// Pays `amount` from `from` to the royalty beneficiaries in `splits`.
+/// `recipients[i]` must be the token account of `splits[i].beneficiary`.
+/// With `gross_up`, the sender also covers any Token-2022 transfer fee so every
+/// beneficiary nets its full share. Returns the transfer fees paid.
+#[allow(clippy::too_many_arguments)]
+pub fn distribute_royalties<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    recipients: &[AccountInfo<'info>],
+    splits: &[RoyaltySplit],
+    payment_mint: &InterfaceAccount<'info, Mint>,
+    amount: u64,
+    gross_up: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    require!(
+        recipients.len() >= splits.len(),
+        CreatorClaimLicenceError::MissingRecipientAccount
+    );
+
+    let mut transfer_fees = 0u64;
+    let shares = split_amounts(amount, splits)?;
+    for ((split, recipient), share) in splits.iter().zip(recipients).zip(shares) {
+        verify_recipient(recipient, split, &payment_mint.key(), token_program.key)?;
+        if share == 0 {
+            continue;
+        }
+
+        msg!("Paying {} to beneficiary {}", share, split.beneficiary);
+        if gross_up {
+            let fee = transfer_tokens_net(token_program, from, payment_mint, recipient, authority, share, signer_seeds)?;
+            transfer_fees = transfer_fees
+                .checked_add(fee)
+                .ok_or(CreatorClaimLicenceError::TransferFeeOverflow)?;
+        } else {
+            transfer_tokens(token_program, from, payment_mint, recipient, authority, share, signer_seeds)?;
+        }
+    }
+
+    Ok(transfer_fees)
+}
+
+/// Skims the platform fee from `amount` into `treasury`, then distributes the rest
+/// across the royalty splits. With `gross_up`, the sender also covers Token-2022
+/// transfer fees on every leg. Returns the platform fee taken and the transfer fees paid.
+#[allow(clippy::too_many_arguments)]
+pub fn pay_fee_and_royalties<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    recipients: &[AccountInfo<'info>],
+    splits: &[RoyaltySplit],
+    payment_mint: &InterfaceAccount<'info, Mint>,
+    fee_bps: u16,
+    amount: u64,
+    gross_up: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(u64, u64)> {
+    let (platform_fee, royalty_amount) = split_fee(amount, fee_bps)?;
+    let mut transfer_fees = 0u64;
+    if platform_fee > 0 {
+        if gross_up {
+            transfer_fees = transfer_tokens_net(token_program, from, payment_mint, treasury, authority, platform_fee, signer_seeds)?;
+        } else {
+            transfer_tokens(token_program, from, payment_mint, treasury, authority, platform_fee, signer_seeds)?;
+        }
+        msg!("Platform fee of {} sent to treasury.", platform_fee);
+    }
+
+    // Rounding dust goes to the first beneficiary so the full amount is always paid out.
+    msg!("Distributing {} tokens across {} royalty split(s).", royalty_amount, splits.len());
+    let royalty_transfer_fees = distribute_royalties(
+        token_program,
+        from,
+        authority,
+        recipients,
+        splits,
+        payment_mint,
+        royalty_amount,
+        gross_up,
+        signer_seeds,
+    )?;
+    let transfer_fees = transfer_fees
+        .checked_add(royalty_transfer_fees)
+        .ok_or(CreatorClaimLicenceError::TransferFeeOverflow)?;
+
+    Ok((platform_fee, transfer_fees))
+}
+
+/// Native SOL counterpart of `pay_fee_and_royalties`: skims the platform fee into
+/// `treasury` and pays the rest to the beneficiary wallets in `recipients`, which must
+/// match `splits` in order. Returns the platform fee taken.
+pub fn pay_fee_and_royalties_lamports<'info>(
+    system_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    treasury: &AccountInfo<'info>,
+    recipients: &[AccountInfo<'info>],
+    splits: &[RoyaltySplit],
+    fee_bps: u16,
+    amount: u64,
+) -> Result<u64> {
+    require!(
+        recipients.len() >= splits.len(),
+        CreatorClaimLicenceError::MissingRecipientAccount
+    );
+
+    let (platform_fee, royalty_amount) = split_fee(amount, fee_bps)?;
+    if platform_fee > 0 {
+        transfer_lamports(system_program, from, treasury, platform_fee)?;
+        msg!("Platform fee of {} lamports sent to treasury.", platform_fee);
+    }
+
+    let shares = split_amounts(royalty_amount, splits)?;
+    for ((split, recipient), share) in splits.iter().zip(recipients).zip(shares) {
+        require_keys_eq!(recipient.key(), split.beneficiary, CreatorClaimLicenceError::SplitMismatch);
+        if share == 0 {
+            continue;
+        }
+
+        msg!("Paying {} lamports to beneficiary {}", share, split.beneficiary);
+        transfer_lamports(system_program, from, recipient, share)?;
+    }
+
+    Ok(platform_fee)
+}
+
+/// Transfers `amount` lamports from a system-owned signer.
+pub fn transfer_lamports<'info>(
+    system_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    system_program::transfer(
+        CpiContext::new(
+            system_program.clone(),
+            system_program::Transfer {
+                from: from.clone(),
+                to: to.clone(),
+            },
+        ),
+        amount,
+    )
+}
+
+/// Transfers `amount` tokens with `transfer_checked`, signing with `signer_seeds` when
+/// `authority` is a PDA. Works with both SPL Token and Token-2022 mints.
+pub fn transfer_tokens<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token_interface::transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            token_interface::TransferChecked {
+                from: from.clone(),
+                mint: mint.to_account_info(),
+                to: to.clone(),
+                authority: authority.clone(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        mint.decimals,
+    )
+}
+
+/// Transfers enough tokens for `to` to receive exactly `net_amount`, adding the mint's
+/// Token-2022 transfer fee on top. Returns the transfer fee paid.
+pub fn transfer_tokens_net<'info>(
+    token_program: &AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    net_amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let transfer_fee = mint_transfer_fee(mint, net_amount)?;
+    let gross_amount = net_amount
+        .checked_add(transfer_fee)
+        .ok_or(CreatorClaimLicenceError::TransferFeeOverflow)?;
+    transfer_tokens(token_program, from, mint, to, authority, gross_amount, signer_seeds)?;
+    Ok(transfer_fee)
+}
+
+/// Closes an emptied token account owned by a PDA, returning its rent to `destination`.
+pub fn close_token_account<'info>(
+    token_program: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    token_interface::close_account(CpiContext::new_with_signer(
+        token_program.clone(),
+        token_interface::CloseAccount {
+            account: account.clone(),
+            destination: destination.clone(),
+            authority: account.clone(),
+        },
+        signer_seeds,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use spl_token_2022::extension::transfer_fee::TransferFee;
+    use spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut};
+
+    fn split(share_bps: u16) -> RoyaltySplit {
+        RoyaltySplit { beneficiary: Pubkey::new_unique(), share_bps }
+    }
+
+    /// Packs a Token-2022 mint with a TransferFee extension charging `fee_bps`, capped at `maximum_fee`.
+    fn transfer_fee_mint(fee_bps: u16, maximum_fee: u64) -> (Vec<u8>, TransferFee) {
+        let space = ExtensionType::try_calculate_account_len::<MintState>(&[ExtensionType::TransferFeeConfig]).unwrap();
+        let mut data = vec![0u8; space];
+        let transfer_fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: fee_bps.into(),
+        };
+
+        let mut state = StateWithExtensionsMut::<MintState>::unpack_uninitialized(&mut data).unwrap();
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = transfer_fee;
+        config.newer_transfer_fee = transfer_fee;
+        state.base = MintState { decimals: 6, is_initialized: true, ..Default::default() };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        (data, transfer_fee)
+    }
+
+    #[test]
+    fn grossed_up_splits_net_every_share_in_full() {
+        let (mint, transfer_fee) = transfer_fee_mint(100, 1_000_000);
+        let splits = [split(3_333), split(3_333), split(3_334)];
+        let (platform_fee, royalty_amount) = split_fee(1_000_003, 250).unwrap();
+
+        let mut legs = vec![platform_fee];
+        legs.extend(split_amounts(royalty_amount, &splits).unwrap());
+
+        let mut received = 0;
+        for net in &legs {
+            let fee = inverse_transfer_fee(&mint, 0, *net).unwrap();
+            assert!(fee > 0);
+            received += transfer_fee.calculate_post_fee_amount(net + fee).unwrap();
+        }
+        assert_eq!(received, 1_000_003);
+    }
+
+    #[test]
+    fn grossed_up_total_adds_the_fee_of_every_leg() {
+        let (mint, _) = transfer_fee_mint(100, 1_000_000);
+        let splits = [split(5_000), split(5_000)];
+        // Legs of 25, 487 and 488: each fee rounds up separately, unlike a single 1% on 1_000
+        let total = grossed_up_total(&mint, 0, 1_000, 250, &splits).unwrap();
+        assert_eq!(total, 1_000 + 1 + 5 + 5);
+
+        let (capped, _) = transfer_fee_mint(100, 2);
+        assert_eq!(grossed_up_total(&capped, 0, 1_000_000, 0, &splits).unwrap(), 1_000_004);
+    }
+
+    #[test]
+    fn transfer_fee_is_capped_at_maximum() {
+        let (mint, _) = transfer_fee_mint(500, 10);
+        assert_eq!(inverse_transfer_fee(&mint, 0, 1_000_000).unwrap(), 10);
+    }
+
+    #[test]
+    fn mints_without_transfer_fee_charge_nothing() {
+        let mut data = vec![0u8; MintState::LEN];
+        let mint = MintState { decimals: 6, is_initialized: true, ..Default::default() };
+        MintState::pack(mint, &mut data).unwrap();
+        assert_eq!(inverse_transfer_fee(&data, 0, 1_000_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn splits_exactly_when_divisible() {
+        let shares = split_amounts(1_000, &[split(7_000), split(3_000)]).unwrap();
+        assert_eq!(shares, vec![700, 300]);
+    }
+
+    #[test]
+    fn remainder_goes_to_first_beneficiary() {
+        let shares = split_amounts(100, &[split(3_333), split(3_333), split(3_334)]).unwrap();
+        assert_eq!(shares, vec![34, 33, 33]);
+        assert_eq!(shares.iter().sum::<u64>(), 100);
+    }
+
+    #[test]
+    fn handles_max_amount_without_overflow() {
+        let shares = split_amounts(u64::MAX, &[split(5_000), split(5_000)]).unwrap();
+        assert_eq!(shares.iter().map(|s| *s as u128).sum::<u128>(), u64::MAX as u128);
+    }
+
+    #[test]
+    fn fee_rounds_down_in_favour_of_beneficiaries() {
+        assert_eq!(split_fee(999, 250).unwrap(), (24, 975));
+        assert_eq!(split_fee(1_000, 0).unwrap(), (0, 1_000));
+        assert_eq!(split_fee(u64::MAX, 10_000).unwrap(), (u64::MAX, 0));
+    }
+
+    #[test]
+    fn rejects_empty_split_table() {
+        let err = split_amounts(100, &[]).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimLicenceError::RoyaltySplitError));
+    }
+}
@@ -1,32 +1,59 @@
 use anchor_lang::prelude::*;
 
-/// Represents the state of a purchased licence.
+/// Represents the state of a purchased licence, or of a sublicence issued from one.
 /// Seeds: ["licence", certificate_details_pk.key().as_ref(), buyer_pk.key().as_ref()]
+/// Sublicence seeds: ["sublicence", parent_licence_pk.key().as_ref(), sublicensee_pk.key().as_ref()]
 /// Size Estimation:
 /// - Discriminator: 8 bytes
 /// - Certificate Details Pubkey: 32 bytes (links to the specific work)
 /// - Buyer Pubkey: 32 bytes
-/// - Purchase Price (USDC): 8 bytes (u64)
+/// - Holder Pubkey: 32 bytes
+/// - Purchase Price: 8 bytes (u64)
+/// - Payment Mint: 32 bytes
+/// - Transfer Fee Paid: 8 bytes (u64)
 /// - Purchase Timestamp: 8 bytes (i64)
 /// - Expiry Timestamp (optional): 1 + 8 = 9 bytes (Option<i64>)
 /// - Status (Active, Revoked): 1 byte (enum)
+/// - Escrow Release At (optional): 1 + 8 = 9 bytes (Option<i64>)
+/// - Renewal Count: 4 bytes (u32)
+/// - Licence Template ID: 2 bytes (u16)
+/// - Parent (optional): 1 + 32 = 33 bytes (Option<Pubkey>)
 /// - Bump: 1 byte
-/// Total ~ 8 + 32 + 32 + 8 + 8 + 9 + 1 + 1 = 99 bytes (very small)
+/// Total ~ 8 + 32 + 32 + 32 + 8 + 32 + 8 + 8 + 9 + 1 + 9 + 4 + 2 + 33 + 1 = 219 bytes (very small)
 #[account]
 #[derive(Debug)]
 pub struct Licence {
     /// The public key of the CertificateDetails account this licence pertains to.
     pub certificate_details: Pubkey,
-    /// The public key of the wallet that purchased the licence.
+    /// The public key of the wallet that purchased the licence. Fixed, as it seeds the PDA.
+    /// For a sublicence, the parent holder that issued it and paid its rent.
     pub buyer: Pubkey,
-    /// The price paid for the licence (e.g., in USDC cents or smallest unit).
+    /// The wallet currently holding the licence. Starts as `buyer`; changed by `transfer_licence`.
+    pub holder: Pubkey,
+    /// The price paid for the licence, in base units of `payment_mint`.
     pub purchase_price: u64,
+    /// The mint the licence was paid in.
+    pub payment_mint: Pubkey,
+    /// Token-2022 transfer fees the buyer paid on top of `purchase_price`, so that
+    /// the treasury and royalty beneficiaries received their shares in full.
+    pub transfer_fee_paid: u64,
     /// Unix timestamp of when the licence was purchased.
     pub purchase_timestamp: i64,
     /// Optional Unix timestamp when the licence expires. None means perpetual.
     pub expiry_timestamp: Option<i64>,
     /// The current status of the licence.
     pub status: LicenceStatus,
+    /// When escrowed payment can be released to the beneficiaries.
+    /// Some only while funds are still held in the licence's escrow account.
+    pub escrow_release_at: Option<i64>,
+    /// How many times the licence has been renewed.
+    pub renewal_count: u32,
+    /// The licence template the licence was issued under.
+    pub licence_template_id: u16,
+    /// The licence this sublicence was issued from. None for purchased licences.
+    /// A sublicence's `status` is only brought in line with its parent by the
+    /// `lapse_sublicence` crank, so readers must check the parent too (see `is_in_force`).
+    pub parent: Option<Pubkey>,
     /// Bump seed for the PDA.
     pub bump: u8,
 }
@@ -36,11 +63,171 @@ impl Licence {
     pub const LEN: usize = 8 // Discriminator
         + 32 // certificate_details (Pubkey)
         + 32 // buyer (Pubkey)
+        + 32 // holder (Pubkey)
         + 8 // purchase_price (u64)
+        + 32 // payment_mint (Pubkey)
+        + 8 // transfer_fee_paid (u64)
         + 8 // purchase_timestamp (i64)
         + 1 + 8 // expiry_timestamp (Option<i64>)
         + 1 // status (enum)
+        + 1 + 8 // escrow_release_at (Option<i64>)
+        + 4 // renewal_count (u32)
+        + 2 // licence_template_id (u16)
+        + 1 + 32 // parent (Option<Pubkey>)
         + 1; // bump (u8)
+
+    /// Populates a freshly purchased, Active licence; `licence_template` decides the term.
+    /// Callers set `transfer_fee_paid`, `escrow_release_at` and `bump` themselves when they apply.
+    pub fn initialize(
+        &mut self,
+        certificate_details: Pubkey,
+        buyer: Pubkey,
+        purchase_price: u64,
+        payment_mint: Pubkey,
+        licence_template: &LicenceTemplate,
+        now: i64,
+    ) -> Result<()> {
+        self.certificate_details = certificate_details;
+        self.buyer = buyer;
+        self.holder = buyer;
+        self.purchase_price = purchase_price;
+        self.payment_mint = payment_mint;
+        self.transfer_fee_paid = 0;
+        self.purchase_timestamp = now;
+        self.expiry_timestamp = licence_template.expiry_from(now)?;
+        self.status = LicenceStatus::Active;
+        self.escrow_release_at = None;
+        self.renewal_count = 0;
+        self.licence_template_id = licence_template.template_id;
+        self.parent = None;
+
+        Ok(())
+    }
+
+    /// Sublicences do not count towards the certificate's active licences.
+    pub fn is_sublicence(&self) -> bool {
+        self.parent.is_some()
+    }
+
+    /// Whether the licence holds a slot in its certificate's active licence count.
+    /// An ended licence keeps its slot until its escrow is released or refunded, so the
+    /// certificate cannot be retired and closed while `release_escrow` still needs it.
+    pub fn counts_as_active(&self) -> bool {
+        !self.is_sublicence() && (self.status == LicenceStatus::Active || self.escrow_release_at.is_some())
+    }
+
+    /// The status the licence has ended with at `now`, or None while it is still running.
+    /// A licence still marked Active past its expiry has ended as Expired, cranked or not.
+    pub fn ended_status(&self, now: i64) -> Option<LicenceStatus> {
+        match self.status {
+            LicenceStatus::Active => match self.expiry_timestamp {
+                Some(expiry) if now > expiry => Some(LicenceStatus::Expired),
+                _ => None,
+            },
+            ref status => Some(status.clone()),
+        }
+    }
+
+    /// Whether the licence grants its rights at `now`. A sublicence also needs its
+    /// parent to be running, since it reads Active until `lapse_sublicence` is cranked;
+    /// `parent` is the loaded parent licence, or None once its account has been closed.
+    /// Ignored for purchased licences.
+    pub fn is_in_force(&self, parent: Option<&Licence>, now: i64) -> bool {
+        if self.ended_status(now).is_some() {
+            return false;
+        }
+        !self.is_sublicence() || parent.is_some_and(|parent| parent.ended_status(now).is_none())
+    }
+}
+
+/// On-chain definition of a licence template (PRD 12.1), managed by the platform admins.
+/// Seeds: ["licence_template", template_id.to_le_bytes()]
+/// Size Estimation:
+/// - Discriminator: 8 bytes
+/// - Template ID: 2 bytes (u16)
+/// - Term (optional): 1 + 8 = 9 bytes (Option<i64>)
+/// - Exclusive: 1 byte (bool)
+/// - Sublicensable: 1 byte (bool)
+/// - Region (optional): 1 + 2 = 3 bytes (Option<[u8; 2]>)
+/// - Transferable: 1 byte (bool)
+/// - Resale Royalty: 2 bytes (u16)
+/// - Active: 1 byte (bool)
+/// - Bump: 1 byte
+/// Total ~ 8 + 2 + 9 + 1 + 1 + 3 + 1 + 2 + 1 + 1 = 29 bytes
+#[account]
+#[derive(Debug)]
+pub struct LicenceTemplate {
+    /// The ID certificates reference in `licence_template_id`.
+    pub template_id: u16,
+    /// Licence term in seconds. None means perpetual.
+    pub term_secs: Option<i64>,
+    /// Whether a licence under this template excludes all other licences.
+    pub exclusive: bool,
+    /// Whether the licensee may grant sublicences.
+    pub sublicensable: bool,
+    /// Optional ISO 3166-1 alpha-2 region the licence is limited to.
+    pub region: Option<[u8; 2]>,
+    /// Whether holders may hand the licence to another wallet with `transfer_licence`.
+    pub transferable: bool,
+    /// Creator royalty charged on transfer, in bps of the licence's purchase price.
+    pub resale_royalty_bps: u16,
+    /// Inactive templates can no longer be purchased or renewed.
+    pub active: bool,
+    /// Bump seed for the PDA.
+    pub bump: u8,
+}
+
+impl LicenceTemplate {
+    pub const LEN: usize = 8 // Discriminator
+        + 2 // template_id (u16)
+        + 1 + 8 // term_secs (Option<i64>)
+        + 1 // exclusive (bool)
+        + 1 // sublicensable (bool)
+        + 1 + 2 // region (Option<[u8; 2]>)
+        + 1 // transferable (bool)
+        + 2 // resale_royalty_bps (u16)
+        + 1 // active (bool)
+        + 1; // bump (u8)
+
+    /// Validates and applies a full set of template parameters.
+    pub fn apply(&mut self, params: LicenceTemplateParams) -> Result<()> {
+        if let Some(term_secs) = params.term_secs {
+            require!(term_secs > 0, CreatorClaimLicenceError::InvalidTemplateTerm);
+        }
+        require!(
+            params.resale_royalty_bps <= 10_000,
+            CreatorClaimLicenceError::InvalidResaleRoyalty
+        );
+
+        self.term_secs = params.term_secs;
+        self.exclusive = params.exclusive;
+        self.sublicensable = params.sublicensable;
+        self.region = params.region;
+        self.transferable = params.transferable;
+        self.resale_royalty_bps = params.resale_royalty_bps;
+        self.active = params.active;
+
+        Ok(())
+    }
+
+    /// Expiry of a licence under this template starting at `start`. None means perpetual.
+    pub fn expiry_from(&self, start: i64) -> Result<Option<i64>> {
+        self.term_secs
+            .map(|term_secs| start.checked_add(term_secs).ok_or(error!(CreatorClaimLicenceError::InvalidTemplateTerm)))
+            .transpose()
+    }
+}
+
+/// Arguments for `create_licence_template` and `update_licence_template`. Replaces every field.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LicenceTemplateParams {
+    pub term_secs: Option<i64>,
+    pub exclusive: bool,
+    pub sublicensable: bool,
+    pub region: Option<[u8; 2]>,
+    pub transferable: bool,
+    pub resale_royalty_bps: u16,
+    pub active: bool,
 }
 
 /// Defines the possible statuses of a licence.
@@ -66,5 +253,207 @@ pub enum CreatorClaimLicenceError {
     BuyerMismatch,
     #[msg("Royalty split calculation error.")]
     RoyaltySplitError,
+    #[msg("Primary recipient account missing from remaining_accounts.")]
+    MissingRecipientAccount,
+    #[msg("Incorrect purchase price provided.")]
+    IncorrectPrice,
+    #[msg("Signer is not authorized to revoke this licence.")]
+    UnauthorizedRevoker,
+    #[msg("Invalid primary recipient account provided.")]
+    InvalidRecipientAccount,
+    #[msg("Certificate has been retired and no longer sells licences.")]
+    CertificateRetired,
+    #[msg("Certificate is suspended by the platform and cannot be licensed.")]
+    CertificateSuspended,
+    #[msg("Payment mint is not accepted by the platform.")]
+    PaymentMintNotAccepted,
+    #[msg("Recipient account does not match the certificate's royalty split.")]
+    SplitMismatch,
+    #[msg("Treasury has no fees to withdraw.")]
+    ZeroBalance,
+    #[msg("Signer is not a platform admin.")]
+    UnauthorizedAdmin,
+    #[msg("Platform requires escrowed purchases; use purchase_licence_escrowed.")]
+    EscrowRequired,
+    #[msg("Licence has no funds held in escrow.")]
+    NotEscrowed,
+    #[msg("Escrow dispute window has not ended yet.")]
+    EscrowLocked,
+    #[msg("Escrow accounts are required to refund this licence.")]
+    MissingEscrowAccounts,
+    #[msg("Refund cannot exceed 10,000 basis points.")]
+    InvalidRefundBps,
+    #[msg("Revoker and buyer token accounts are required to refund this licence.")]
+    MissingRefundAccounts,
+    #[msg("Licence has not reached its expiry timestamp.")]
+    LicenceNotExpired,
+    #[msg("Licence still has funds in escrow; release them before closing.")]
+    EscrowPending,
+    #[msg("Perpetual licences cannot be renewed.")]
+    PerpetualLicence,
+    #[msg("Renewed expiry is out of range.")]
+    InvalidExtension,
+    #[msg("Licence template is inactive or does not match the certificate.")]
+    ExpiredTemplate,
+    #[msg("Licence template term must be positive.")]
+    InvalidTemplateTerm,
+    #[msg("Another licence holds exclusive rights to this work.")]
+    ExclusiveLicenceActive,
+    #[msg("Resale royalty cannot exceed 10,000 basis points.")]
+    InvalidResaleRoyalty,
+    #[msg("Licence template does not allow transfers.")]
+    NotTransferable,
+    #[msg("Signer is not the current licence holder.")]
+    HolderMismatch,
+    #[msg("Token accounts required to take payment are missing.")]
+    MissingPaymentAccounts,
+    #[msg("Licence template does not allow sublicensing.")]
+    NotSublicensable,
+    #[msg("Operation is not available for sublicences.")]
+    IsSublicence,
+    #[msg("Licence is not a sublicence of the given parent.")]
+    ParentMismatch,
+    #[msg("Parent licence is still active.")]
+    ParentLicenceActive,
+    #[msg("Payment mint does not match the certificate's listing.")]
+    PaymentMintMismatch,
+    #[msg("Certificate does not accept payment in SOL.")]
+    LamportsNotAccepted,
+    #[msg("Transfer fee cannot be computed for this amount.")]
+    TransferFeeOverflow,
+    #[msg("Listed price exceeds the buyer's maximum price.")]
+    SlippageExceeded,
     // Add other specific errors as needed
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(term_secs: Option<i64>) -> LicenceTemplate {
+        LicenceTemplate {
+            template_id: 4,
+            term_secs,
+            exclusive: false,
+            sublicensable: false,
+            region: None,
+            transferable: false,
+            resale_royalty_bps: 0,
+            active: true,
+            bump: 255,
+        }
+    }
+
+    fn params(term_secs: Option<i64>) -> LicenceTemplateParams {
+        LicenceTemplateParams {
+            term_secs,
+            exclusive: true,
+            sublicensable: true,
+            region: Some(*b"GB"),
+            transferable: true,
+            resale_royalty_bps: 500,
+            active: true,
+        }
+    }
+
+    fn licence(status: LicenceStatus, escrow_release_at: Option<i64>, parent: Option<Pubkey>) -> Licence {
+        Licence {
+            certificate_details: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            holder: Pubkey::new_unique(),
+            purchase_price: 100,
+            payment_mint: Pubkey::new_unique(),
+            transfer_fee_paid: 0,
+            purchase_timestamp: 1_000,
+            expiry_timestamp: Some(2_000),
+            status,
+            escrow_release_at,
+            renewal_count: 0,
+            licence_template_id: 4,
+            parent,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn initialize_starts_an_active_licence_held_by_the_buyer() {
+        let mut l = licence(LicenceStatus::Revoked, Some(1_500), Some(Pubkey::new_unique()));
+        let (certificate_details, buyer, mint) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        l.initialize(certificate_details, buyer, 250, mint, &template(Some(86_400)), 10_000).unwrap();
+
+        assert_eq!((l.buyer, l.holder), (buyer, buyer));
+        assert_eq!(l.certificate_details, certificate_details);
+        assert_eq!((l.purchase_price, l.payment_mint, l.transfer_fee_paid), (250, mint, 0));
+        assert_eq!((l.purchase_timestamp, l.expiry_timestamp), (10_000, Some(96_400)));
+        assert_eq!(l.status, LicenceStatus::Active);
+        assert_eq!((l.escrow_release_at, l.parent, l.renewal_count), (None, None, 0));
+        assert_eq!(l.licence_template_id, 4);
+    }
+
+    #[test]
+    fn ended_licence_stays_counted_while_escrow_is_pending() {
+        assert!(licence(LicenceStatus::Active, None, None).counts_as_active());
+        assert!(licence(LicenceStatus::Expired, Some(1_500), None).counts_as_active());
+        assert!(licence(LicenceStatus::Revoked, Some(1_500), None).counts_as_active());
+        assert!(!licence(LicenceStatus::Expired, None, None).counts_as_active());
+        assert!(!licence(LicenceStatus::Revoked, None, None).counts_as_active());
+        assert!(!licence(LicenceStatus::Active, None, Some(Pubkey::new_unique())).counts_as_active());
+    }
+
+    #[test]
+    fn active_licence_past_expiry_has_ended_as_expired() {
+        assert_eq!(licence(LicenceStatus::Active, None, None).ended_status(2_000), None);
+        assert_eq!(licence(LicenceStatus::Active, None, None).ended_status(2_001), Some(LicenceStatus::Expired));
+        assert_eq!(licence(LicenceStatus::Revoked, None, None).ended_status(1_500), Some(LicenceStatus::Revoked));
+    }
+
+    #[test]
+    fn sublicence_is_only_in_force_while_its_parent_runs() {
+        let sublicence = licence(LicenceStatus::Active, None, Some(Pubkey::new_unique()));
+        let parent = licence(LicenceStatus::Active, None, None);
+        assert!(sublicence.is_in_force(Some(&parent), 1_500));
+        // Not yet lapsed, but the parent has been revoked, has run out or has been closed
+        assert!(!sublicence.is_in_force(Some(&licence(LicenceStatus::Revoked, None, None)), 1_500));
+        assert!(!sublicence.is_in_force(Some(&Licence { expiry_timestamp: Some(1_000), ..parent }), 1_500));
+        assert!(!sublicence.is_in_force(None, 1_500));
+        assert!(licence(LicenceStatus::Active, None, None).is_in_force(None, 1_500));
+    }
+
+    #[test]
+    fn perpetual_template_has_no_expiry() {
+        assert_eq!(template(None).expiry_from(1_000).unwrap(), None);
+    }
+
+    #[test]
+    fn limited_template_expires_after_term() {
+        assert_eq!(template(Some(86_400)).expiry_from(1_000).unwrap(), Some(87_400));
+    }
+
+    #[test]
+    fn expiry_overflow_is_rejected() {
+        let err = template(Some(i64::MAX)).expiry_from(1).unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimLicenceError::InvalidTemplateTerm));
+    }
+
+    #[test]
+    fn apply_rejects_non_positive_term() {
+        let mut t = template(None);
+        for term in [0, -1] {
+            let err = t.apply(params(Some(term))).unwrap_err();
+            assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimLicenceError::InvalidTemplateTerm));
+        }
+        t.apply(params(Some(3_600))).unwrap();
+        assert_eq!(t.term_secs, Some(3_600));
+        assert_eq!(t.region, Some(*b"GB"));
+        assert!(t.exclusive && t.sublicensable);
+    }
+
+    #[test]
+    fn apply_rejects_resale_royalty_above_100_percent() {
+        let mut t = template(None);
+        let err = t
+            .apply(LicenceTemplateParams { resale_royalty_bps: 10_001, ..params(None) })
+            .unwrap_err();
+        assert_eq!(err, anchor_lang::error::Error::from(CreatorClaimLicenceError::InvalidResaleRoyalty));
+    }
+}
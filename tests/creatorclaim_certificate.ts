@@ -1,6 +1,26 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, web3 } from "@coral-xyz/anchor";
 import { CreatorclaimCertificate } from "../target/types/creatorclaim_certificate";
+import { createMint } from "@solana/spl-token";
+import {
+  PROGRAM_ID as BUBBLEGUM_PROGRAM_ID,
+  MetadataArgs,
+  TokenProgramVersion,
+  TokenStandard,
+  computeCompressedNFTHash,
+  computeCreatorHash,
+  computeDataHash,
+  createCreateTreeInstruction,
+  createMintV1Instruction,
+  getLeafAssetId,
+} from "@metaplex-foundation/mpl-bubblegum";
+import {
+  ConcurrentMerkleTreeAccount,
+  MerkleTree,
+  SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+  SPL_NOOP_PROGRAM_ID,
+  createAllocTreeIx,
+} from "@solana/spl-account-compression";
 import { assert } from "chai";
 import "mocha";
 
@@ -19,6 +39,10 @@ interface RegisterCertificateInput {
   royaltySplits: RoyaltySplitInput[];
 }
 
+// Derives the permanent AssetRegistration marker created alongside every certificate
+const assetRegistrationPda = (assetKey: web3.PublicKey, programId: web3.PublicKey): web3.PublicKey =>
+  web3.PublicKey.findProgramAddressSync([Buffer.from("asset_registration"), assetKey.toBuffer()], programId)[0];
+
 // Helper function to generate a random SHA-256 hash (as [u8; 32])
 const generateRandomHash = (): number[] => {
   const buffer = new Uint8Array(32);
@@ -42,6 +66,8 @@ describe("creatorclaim_certificate", () => {
 
   // Use a keypair for the 'asset_id' - in reality, this would likely be the mint address of a cNFT
   const assetId = anchor.web3.Keypair.generate();
+  // Stand-in for the USDC mint listings are priced in; the certificate program only stores it
+  const usdcMint = anchor.web3.Keypair.generate().publicKey;
 
   it("Is initialized with price!", async () => {
     // Generate test data
@@ -69,11 +95,14 @@ describe("creatorclaim_certificate", () => {
           testData.metadataUriHash,
           testData.licenceTemplateId,
           testData.price,
-          testData.royaltySplits
+          usdcMint,
+          testData.royaltySplits,
+          { unverified: {} }
       )
       .accounts({
         creator: creator.publicKey,
         certificateDetails: certificateDetailsPDA,
+        assetRegistration: assetRegistrationPda(assetId.publicKey, program.programId),
         assetIdOrMintPk: assetId.publicKey,
         systemProgram: anchor.web3.SystemProgram.programId,
       })
@@ -87,8 +116,10 @@ describe("creatorclaim_certificate", () => {
     // Assertions
     assert.ok(accountData.authority.equals(creator.publicKey));
     assert.deepEqual(accountData.metadataUriHash, testData.metadataUriHash);
+    assert.equal(accountData.metadataVersion, 1);
     assert.equal(accountData.licenceTemplateId, testData.licenceTemplateId);
     assert.ok(accountData.price.eq(testData.price), `Price mismatch: ${accountData.price} vs ${testData.price}`);
+    assert.ok(accountData.paymentMint.equals(usdcMint));
     assert.equal(accountData.royaltySplits.length, 1);
     assert.ok(accountData.royaltySplits[0].beneficiary.equals(creator.publicKey));
     assert.equal(accountData.royaltySplits[0].shareBps, 10000);
@@ -114,8 +145,8 @@ describe("creatorclaim_certificate", () => {
     );
     try {
         await program.methods
-            .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, testData.royaltySplits)
-            .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+            .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, usdcMint, testData.royaltySplits, { unverified: {} })
+            .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
             .rpc();
         assert.fail("Transaction should have failed due to zero price.");
     } catch (err) {
@@ -143,8 +174,8 @@ describe("creatorclaim_certificate", () => {
     );
     try {
       await program.methods
-        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, testData.royaltySplits)
-        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, usdcMint, testData.royaltySplits, { unverified: {} })
+        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
         .rpc();
       assert.fail("Transaction should have failed due to invalid royalty sum.");
     } catch (err) {
@@ -180,8 +211,8 @@ describe("creatorclaim_certificate", () => {
     );
     try {
       await program.methods
-        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, testData.royaltySplits)
-        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, usdcMint, testData.royaltySplits, { unverified: {} })
+        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
         .rpc();
       assert.fail("Transaction should have failed due to too many recipients.");
     } catch (err) {
@@ -205,8 +236,8 @@ describe("creatorclaim_certificate", () => {
     );
     try {
       await program.methods
-        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, testData.royaltySplits)
-        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .registerCertificate(testData.metadataUriHash, testData.licenceTemplateId, testData.price, usdcMint, testData.royaltySplits, { unverified: {} })
+        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
         .rpc();
       assert.fail("Transaction should have failed due to missing metadata hash.");
     } catch (err) {
@@ -215,4 +246,627 @@ describe("creatorclaim_certificate", () => {
     }
   });
 
-});
\ No newline at end of file
+  // Test: Two-step authority transfer
+  it("Transfers authority after the nominee accepts", async () => {
+    const newAuthority = anchor.web3.Keypair.generate();
+    const [certificateDetailsPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .rpc();
+
+    let accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.ok(accountData.authority.equals(creator.publicKey), "Authority should not change before acceptance");
+    assert.ok(accountData.pendingAuthority.equals(newAuthority.publicKey));
+
+    // An unrelated signer cannot accept the transfer
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .acceptAuthority()
+        .accounts({ newAuthority: stranger.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Transaction should have failed for a signer that was not nominated.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "PendingAuthorityMismatch");
+    }
+
+    await program.methods
+      .acceptAuthority()
+      .accounts({ newAuthority: newAuthority.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .signers([newAuthority])
+      .rpc();
+
+    accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.ok(accountData.authority.equals(newAuthority.publicKey));
+    assert.isNull(accountData.pendingAuthority);
+
+    // The previous authority has lost control; hand the certificate back for later tests
+    try {
+      await program.methods
+        .proposeAuthority(creator.publicKey)
+        .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+        .rpc();
+      assert.fail("Transaction should have failed for the previous authority.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotAuthorised");
+    }
+    await program.methods
+      .proposeAuthority(creator.publicKey)
+      .accounts({ authority: newAuthority.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accounts({ newAuthority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .rpc();
+    console.log("Authority transferred and handed back successfully.");
+  });
+
+  // Test: Metadata hash revisions
+  it("Updates the metadata hash and bumps the version", async () => {
+    const [certificateDetailsPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.publicKey.toBuffer()],
+      program.programId
+    );
+    const before = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    const newHash = generateRandomHash();
+
+    await program.methods
+      .updateMetadataHash(newHash)
+      .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .rpc();
+
+    const after = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.deepEqual(after.metadataUriHash, newHash);
+    assert.equal(after.metadataVersion, before.metadataVersion + 1);
+
+    // Re-submitting the same hash is rejected
+    try {
+      await program.methods
+        .updateMetadataHash(newHash)
+        .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+        .rpc();
+      assert.fail("Transaction should have failed for an unchanged metadata hash.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "MetadataHashUnchanged");
+      console.log("Successfully caught MetadataHashUnchanged error.");
+    }
+  });
+
+  // Test: Repricing and switching template
+  it("Updates the listing price and template", async () => {
+    const [certificateDetailsPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.publicKey.toBuffer()],
+      program.programId
+    );
+    const newPrice = new anchor.BN(35 * 10**6); // Sale price: 35 USDC
+    const newTemplateId = 2; // Editorial-Only
+    const eurcMint = anchor.web3.Keypair.generate().publicKey; // Reprice in EURC
+
+    await program.methods
+      .updateListing(newPrice, eurcMint, newTemplateId)
+      .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+      .rpc();
+
+    const accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.ok(accountData.price.eq(newPrice));
+    assert.ok(accountData.paymentMint.equals(eurcMint));
+    assert.equal(accountData.licenceTemplateId, newTemplateId);
+    assert.equal(accountData.activeLicences, 0);
+
+    try {
+      await program.methods
+        .updateListing(new anchor.BN(0), eurcMint, newTemplateId)
+        .accounts({ authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey })
+        .rpc();
+      assert.fail("Transaction should have failed due to zero price.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ZeroPriceNotAllowed");
+      console.log("Successfully caught ZeroPriceNotAllowed error on update_listing.");
+    }
+  });
+
+  // Test: Opting in and out of SOL payments
+  it("Sets and clears the SOL price", async () => {
+    const [certificateDetailsPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.publicKey.toBuffer()],
+      program.programId
+    );
+    const accounts = { authority: creator.publicKey, certificateDetails: certificateDetailsPDA, assetIdOrMintPk: assetId.publicKey };
+
+    let accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.isNull(accountData.lamportPrice);
+
+    const lamportPrice = new anchor.BN(0.25 * anchor.web3.LAMPORTS_PER_SOL);
+    await program.methods.setLamportPrice(lamportPrice).accounts(accounts).rpc();
+    accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.ok(accountData.lamportPrice.eq(lamportPrice));
+
+    try {
+      await program.methods.setLamportPrice(new anchor.BN(0)).accounts(accounts).rpc();
+      assert.fail("Transaction should have failed due to zero SOL price.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ZeroPriceNotAllowed");
+    }
+
+    await program.methods.setLamportPrice(null).accounts(accounts).rpc();
+    accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.isNull(accountData.lamportPrice);
+  });
+
+  // Test: Royalty split amendments require consent from beneficiaries losing share
+  it("Amends royalty splits only with consent of reduced beneficiaries", async () => {
+    const bandMember = anchor.web3.Keypair.generate();
+    const testAssetId = anchor.web3.Keypair.generate();
+    const [pda, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), testAssetId.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerCertificate(generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint, [
+        { beneficiary: creator.publicKey, shareBps: 5000 },
+        { beneficiary: bandMember.publicKey, shareBps: 5000 },
+      ], { unverified: {} })
+      .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
+      .rpc();
+
+    const newSplits: RoyaltySplitInput[] = [{ beneficiary: creator.publicKey, shareBps: 10000 }];
+
+    try {
+      await program.methods
+        .amendRoyaltySplits(newSplits)
+        .accounts({ authority: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey })
+        .rpc();
+      assert.fail("Transaction should have failed without the band member's signature.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "MissingBeneficiaryConsent");
+      console.log("Successfully caught MissingBeneficiaryConsent error.");
+    }
+
+    await program.methods
+      .amendRoyaltySplits(newSplits)
+      .accounts({ authority: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey })
+      .remainingAccounts([{ pubkey: bandMember.publicKey, isSigner: true, isWritable: false }])
+      .signers([bandMember])
+      .rpc();
+
+    const accountData = await program.account.certificateDetails.fetch(pda);
+    assert.equal(accountData.royaltySplits.length, 1);
+    assert.ok(accountData.royaltySplits[0].beneficiary.equals(creator.publicKey));
+    assert.equal(accountData.royaltySplits[0].shareBps, 10000);
+  });
+
+  // Test: Opt-in verification of mint-backed assets
+  it("Registers a certificate against a mint only for its mint authority", async () => {
+    const otherAuthority = anchor.web3.Keypair.generate();
+    const ownMint = await createMint(provider.connection, creator.payer, creator.publicKey, null, 0);
+    const foreignMint = await createMint(provider.connection, creator.payer, otherAuthority.publicKey, null, 0);
+    const splits = [{ beneficiary: creator.publicKey, shareBps: 10000 }];
+
+    const [ownPda, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), ownMint.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerCertificate(generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint, splits, { mint: {} })
+      .accounts({ creator: creator.publicKey, certificateDetails: ownPda, assetIdOrMintPk: ownMint, assetRegistration: assetRegistrationPda(ownMint, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
+      .rpc();
+    const accountData = await program.account.certificateDetails.fetch(ownPda);
+    assert.isTrue(accountData.assetVerified);
+
+    const [foreignPda, __] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), foreignMint.toBuffer()],
+      program.programId
+    );
+    try {
+      await program.methods
+        .registerCertificate(generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint, splits, { mint: {} })
+        .accounts({ creator: creator.publicKey, certificateDetails: foreignPda, assetIdOrMintPk: foreignMint, assetRegistration: assetRegistrationPda(foreignMint, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+      assert.fail("Transaction should have failed for a mint controlled by someone else.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotMintAuthority");
+      console.log("Successfully caught NotMintAuthority error.");
+    }
+  });
+
+  // Test: Registering a cNFT minted outside the program with a Bubblegum leaf proof
+  it("Registers a compressed asset only with a valid leaf proof for its owner", async () => {
+    // Depth 3 tree with no canopy, so the proof carries all three sibling nodes
+    const merkleTree = anchor.web3.Keypair.generate();
+    const [treeAuthority, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [merkleTree.publicKey.toBuffer()],
+      BUBBLEGUM_PROGRAM_ID
+    );
+    const allocTreeIx = await createAllocTreeIx(
+      provider.connection,
+      merkleTree.publicKey,
+      creator.publicKey,
+      { maxDepth: 3, maxBufferSize: 8 },
+      0
+    );
+    const createTreeIx = createCreateTreeInstruction(
+      {
+        treeAuthority,
+        merkleTree: merkleTree.publicKey,
+        payer: creator.publicKey,
+        treeCreator: creator.publicKey,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+      },
+      { maxDepth: 3, maxBufferSize: 8, public: false }
+    );
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(allocTreeIx, createTreeIx), [merkleTree]);
+
+    // Mint leaf 0 straight through Bubblegum, owned by the creator
+    const metadata: MetadataArgs = {
+      name: "Bubblegum original",
+      symbol: "CCLM",
+      uri: "https://example.com/originals/0.json",
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: true,
+      editionNonce: null,
+      tokenStandard: TokenStandard.NonFungible,
+      collection: null,
+      uses: null,
+      tokenProgramVersion: TokenProgramVersion.Original,
+      creators: [],
+    };
+    const mintIx = createMintV1Instruction(
+      {
+        treeAuthority,
+        leafOwner: creator.publicKey,
+        leafDelegate: creator.publicKey,
+        merkleTree: merkleTree.publicKey,
+        payer: creator.publicKey,
+        treeDelegate: creator.publicKey,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+      },
+      { message: metadata }
+    );
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(mintIx));
+
+    // Rebuild the tree locally to get the proof, and check it against the on-chain root
+    const nonce = new anchor.BN(0);
+    const assetKey = await getLeafAssetId(merkleTree.publicKey, nonce);
+    const leaf = computeCompressedNFTHash(assetKey, creator.publicKey, creator.publicKey, nonce, metadata);
+    const localTree = new MerkleTree([leaf, ...Array(7).fill(Buffer.alloc(32))]);
+    const { proof: proofNodes, root } = localTree.getProof(0);
+    const treeAccount = await ConcurrentMerkleTreeAccount.fromAccountAddress(provider.connection, merkleTree.publicKey);
+    assert.ok(treeAccount.getCurrentRoot().equals(root));
+
+    const proof = {
+      root: Array.from(root),
+      delegate: creator.publicKey,
+      dataHash: Array.from(computeDataHash(metadata)),
+      creatorHash: Array.from(computeCreatorHash([])),
+      nonce,
+      index: 0,
+    };
+    const [certificateDetails, __] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetKey.toBuffer()],
+      program.programId
+    );
+    const register = (signer: web3.Keypair | null, leafProof: typeof proof) => {
+      const owner = signer ? signer.publicKey : creator.publicKey;
+      const builder = program.methods
+        .registerCertificate(
+          generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint,
+          [{ beneficiary: owner, shareBps: 10000 }],
+          { compressedLeaf: { 0: leafProof } }
+        )
+        .accounts({
+          creator: owner,
+          certificateDetails,
+          assetRegistration: assetRegistrationPda(assetKey, program.programId),
+          assetIdOrMintPk: assetKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          merkleTree: merkleTree.publicKey,
+          compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+        })
+        .remainingAccounts(proofNodes.map((node) => ({ pubkey: new web3.PublicKey(node), isSigner: false, isWritable: false })));
+      return signer ? builder.signers([signer]).rpc() : builder.rpc();
+    };
+
+    // A wallet that does not own the leaf cannot reuse the owner's proof
+    const outsider = anchor.web3.Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(outsider.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop, "confirmed");
+    try {
+      await register(outsider, proof);
+      assert.fail("Transaction should have failed for a wallet that does not own the leaf.");
+    } catch (err) {
+      // Raised by the compression program when the rebuilt leaf does not hash to the root
+      assert.equal(err.error.errorCode.code, "ConcurrentMerkleTreeError");
+    }
+
+    // Neither can the owner claim leaf contents that are not in the tree
+    try {
+      await register(null, { ...proof, dataHash: generateRandomHash() });
+      assert.fail("Transaction should have failed for a leaf that is not in the tree.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ConcurrentMerkleTreeError");
+    }
+
+    // Nor a proof for a different leaf than the asset id
+    try {
+      await register(null, { ...proof, nonce: new anchor.BN(1), index: 1 });
+      assert.fail("Transaction should have failed for a proof of another leaf.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "AssetIdMismatch");
+    }
+    assert.isNull(await program.account.certificateDetails.fetchNullable(certificateDetails));
+
+    await register(null, proof);
+    const accountData = await program.account.certificateDetails.fetch(certificateDetails);
+    assert.isTrue(accountData.assetVerified);
+    assert.ok(accountData.authority.equals(creator.publicKey));
+  });
+
+  // Test: Minting the certificate cNFT and registering it in one transaction
+  it("Mints a certificate cNFT into a tree until the tree is full", async () => {
+    // Smallest valid tree: depth 3 holds 8 leaves
+    const merkleTree = anchor.web3.Keypair.generate();
+    const [treeAuthority, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [merkleTree.publicKey.toBuffer()],
+      BUBBLEGUM_PROGRAM_ID
+    );
+    const allocTreeIx = await createAllocTreeIx(
+      provider.connection,
+      merkleTree.publicKey,
+      creator.publicKey,
+      { maxDepth: 3, maxBufferSize: 8 },
+      0
+    );
+    const createTreeIx = createCreateTreeInstruction(
+      {
+        treeAuthority,
+        merkleTree: merkleTree.publicKey,
+        payer: creator.publicKey,
+        treeCreator: creator.publicKey,
+        logWrapper: SPL_NOOP_PROGRAM_ID,
+        compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+      },
+      { maxDepth: 3, maxBufferSize: 8, public: false }
+    );
+    await provider.sendAndConfirm(new anchor.web3.Transaction().add(allocTreeIx, createTreeIx), [merkleTree]);
+
+    const metadata = (index: number) => ({
+      name: `CreatorClaim #${index}`,
+      symbol: "CCLM",
+      uri: `https://example.com/certificates/${index}.json`,
+      sellerFeeBasisPoints: 0,
+      primarySaleHappened: false,
+      isMutable: true,
+      editionNonce: null,
+      tokenStandard: { nonFungible: {} },
+      collection: null,
+      uses: null,
+      tokenProgramVersion: { original: {} },
+      creators: [],
+    });
+    const splits = [{ beneficiary: creator.publicKey, shareBps: 10000 }];
+    const mint = async (leafIndex: number) => {
+      const assetKey = await getLeafAssetId(merkleTree.publicKey, new anchor.BN(leafIndex));
+      const [certificateDetails, __] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("certificate_details"), assetKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .mintCertificate(1, new anchor.BN(50 * 10**6), usdcMint, splits, metadata(leafIndex))
+        .accounts({
+          creator: creator.publicKey,
+          certificateDetails,
+          assetRegistration: assetRegistrationPda(assetKey, program.programId),
+          assetIdOrMintPk: assetKey,
+          treeAuthority,
+          merkleTree: merkleTree.publicKey,
+          bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+          logWrapper: SPL_NOOP_PROGRAM_ID,
+          compressionProgram: SPL_ACCOUNT_COMPRESSION_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+      return certificateDetails;
+    };
+
+    // The first leaf gets nonce 0; claiming the id of a later leaf is refused
+    try {
+      await mint(1);
+      assert.fail("Transaction should have failed for an asset id the leaf will not receive.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "AssetIdMismatch");
+    }
+
+    // A squatter registers the next asset id Unverified before it is minted
+    const squatter = anchor.web3.Keypair.generate();
+    const airdrop = await provider.connection.requestAirdrop(squatter.publicKey, web3.LAMPORTS_PER_SOL);
+    await provider.connection.confirmTransaction(airdrop, "confirmed");
+    const nextAssetKey = await getLeafAssetId(merkleTree.publicKey, new anchor.BN(0));
+    const [squattedPda, ___] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), nextAssetKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerCertificate(generateRandomHash(), 1, new anchor.BN(1), usdcMint, [{ beneficiary: squatter.publicKey, shareBps: 10000 }], { unverified: {} })
+      .accounts({ creator: squatter.publicKey, certificateDetails: squattedPda, assetRegistration: assetRegistrationPda(nextAssetKey, program.programId), assetIdOrMintPk: nextAssetKey, systemProgram: anchor.web3.SystemProgram.programId })
+      .signers([squatter])
+      .rpc();
+
+    // Minting takes the squatted listing over
+    const firstCertificate = await mint(0);
+    assert.ok(firstCertificate.equals(squattedPda));
+    const accountData = await program.account.certificateDetails.fetch(firstCertificate);
+    assert.isTrue(accountData.assetVerified);
+    assert.ok(accountData.authority.equals(creator.publicKey));
+    assert.ok(accountData.price.eq(new anchor.BN(50 * 10**6)));
+    assert.ok(accountData.royaltySplits[0].beneficiary.equals(creator.publicKey));
+
+    for (let leafIndex = 1; leafIndex < 8; leafIndex++) {
+      await mint(leafIndex);
+    }
+    try {
+      await mint(8);
+      assert.fail("Transaction should have failed for a full tree.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "TreeCapacity");
+      console.log("Successfully caught TreeCapacity error.");
+    }
+  });
+
+  // Test: Retiring a certificate with no active licences closes it
+  it("Retires a certificate and reclaims rent", async () => {
+    const testAssetId = anchor.web3.Keypair.generate();
+    const [pda, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), testAssetId.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .registerCertificate(generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint, [{ beneficiary: creator.publicKey, shareBps: 10000 }], { unverified: {} })
+      .accounts({ creator: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), systemProgram: anchor.web3.SystemProgram.programId })
+      .rpc();
+
+    await program.methods
+      .retireCertificate()
+      .accounts({ authority: creator.publicKey, certificateDetails: pda, assetIdOrMintPk: testAssetId.publicKey })
+      .rpc();
+
+    const accountData = await program.account.certificateDetails.fetchNullable(pda);
+    assert.isNull(accountData, "Certificate account should be closed when no licences are active");
+
+    // The registration marker outlives the certificate, so the asset cannot be registered again
+    const registration = await program.account.assetRegistration.fetch(assetRegistrationPda(testAssetId.publicKey, program.programId));
+    assert.ok(registration.bump);
+    try {
+      await program.methods
+        .registerCertificate(generateRandomHash(), 1, new anchor.BN(50 * 10**6), usdcMint, [{ beneficiary: creator.publicKey, shareBps: 10000 }], { unverified: {} })
+        .accounts({ creator: creator.publicKey, certificateDetails: pda, assetRegistration: assetRegistrationPda(testAssetId.publicKey, program.programId), assetIdOrMintPk: testAssetId.publicKey, systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+      assert.fail("Transaction should have failed for an asset that was registered before.");
+    } catch (err) {
+      assert.include(err.toString(), "already in use");
+      console.log("Successfully refused to re-register a retired asset.");
+    }
+  });
+
+  // Test: Admin suspension and reinstatement
+  it("Suspends and reinstates a certificate via the platform admin", async () => {
+    const [platformConfigPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_config")],
+      program.programId
+    );
+    const [programDataPDA, __] = anchor.web3.PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+    );
+    const [certificateDetailsPDA, ___] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // The test wallet deployed the program, so it is the upgrade authority
+    await program.methods
+      .initializeConfig({
+        admins: [creator.publicKey],
+        treasury: creator.publicKey,
+        feeBps: 250,
+        acceptedPaymentMints: [],
+        licenceProgram: anchor.workspace.CreatorclaimLicence.programId,
+        escrowPeriod: new anchor.BN(0),
+      })
+      .accounts({
+        upgradeAuthority: creator.publicKey,
+        platformConfig: platformConfigPDA,
+        program: program.programId,
+        programData: programDataPDA,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const adminAccounts = {
+      admin: creator.publicKey,
+      platformConfig: platformConfigPDA,
+      certificateDetails: certificateDetailsPDA,
+      assetIdOrMintPk: assetId.publicKey,
+    };
+
+    await program.methods.suspendCertificate().accounts(adminAccounts).rpc();
+    let accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.equal(JSON.stringify(accountData.status), JSON.stringify({ suspended: {} }));
+
+    // A non-admin cannot lift the suspension
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .reinstateCertificate()
+        .accounts({ ...adminAccounts, admin: stranger.publicKey })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Transaction should have failed for a non-admin signer.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotAuthorised");
+    }
+
+    await program.methods.reinstateCertificate().accounts(adminAccounts).rpc();
+    accountData = await program.account.certificateDetails.fetch(certificateDetailsPDA);
+    assert.equal(JSON.stringify(accountData.status), JSON.stringify({ active: {} }));
+  });
+
+  // Test: Rotating platform admins through update_config
+  it("Lets an admin update the platform config and rejects outsiders", async () => {
+    const [platformConfigPDA, _] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("platform_config")],
+      program.programId
+    );
+    const newAdmin = anchor.web3.Keypair.generate();
+    const params = {
+      admins: [creator.publicKey, newAdmin.publicKey],
+      treasury: creator.publicKey,
+      feeBps: 500,
+      acceptedPaymentMints: [],
+      licenceProgram: anchor.workspace.CreatorclaimLicence.programId,
+      escrowPeriod: new anchor.BN(14 * 24 * 60 * 60),
+    };
+
+    await program.methods
+      .updateConfig(params)
+      .accounts({ admin: creator.publicKey, platformConfig: platformConfigPDA })
+      .rpc();
+
+    const config = await program.account.platformConfig.fetch(platformConfigPDA);
+    assert.equal(config.feeBps, 500);
+    assert.equal(config.admins.length, 2);
+    assert.ok(config.admins[1].equals(newAdmin.publicKey));
+
+    const stranger = anchor.web3.Keypair.generate();
+    try {
+      await program.methods
+        .updateConfig({ ...params, admins: [stranger.publicKey] })
+        .accounts({ admin: stranger.publicKey, platformConfig: platformConfigPDA })
+        .signers([stranger])
+        .rpc();
+      assert.fail("Transaction should have failed for a non-admin signer.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotAuthorised");
+    }
+
+    try {
+      await program.methods
+        .updateConfig({ ...params, feeBps: 10001 })
+        .accounts({ admin: creator.publicKey, platformConfig: platformConfigPDA })
+        .rpc();
+      assert.fail("Transaction should have failed for a fee above 100%.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "InvalidFeeBps");
+    }
+  });
+
+});
@@ -1,13 +1,20 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program, web3, BN } from "@coral-xyz/anchor";
 import { CreatorclaimLicence } from "../target/types/creatorclaim_licence";
+import { CreatorclaimCertificate } from "../target/types/creatorclaim_certificate";
 import {
     TOKEN_PROGRAM_ID,
+    TOKEN_2022_PROGRAM_ID,
     ASSOCIATED_TOKEN_PROGRAM_ID,
     createMint,
     createAccount,
     mintTo,
-    getAccount
+    getAccount,
+    getMintLen,
+    createInitializeMintInstruction,
+    createInitializeTransferFeeConfigInstruction,
+    ExtensionType,
+    NATIVE_MINT
 } from "@solana/spl-token"; // Using @solana/spl-token for setup
 import { assert } from "chai";
 import "mocha";
@@ -15,6 +22,16 @@ import "mocha";
 // Helper function to delay execution
 const delay = (ms: number) => new Promise(res => setTimeout(res, ms));
 
+// Helper function to generate a random SHA-256 hash (as [u8; 32])
+const generateRandomHash = (): number[] => {
+  const buffer = new Uint8Array(32);
+  for (let i = 0; i < 32; i++) {
+    buffer[i] = Math.floor(Math.random() * 256);
+  }
+  buffer[0] = buffer[0] || 1; // Never all zeros
+  return Array.from(buffer);
+};
+
 describe("creatorclaim_licence", () => {
   // Configure the client to use the local cluster.
   const provider = anchor.AnchorProvider.env();
@@ -22,26 +39,279 @@ describe("creatorclaim_licence", () => {
   const connection = provider.connection;
 
   const program = anchor.workspace.CreatorclaimLicence as Program<CreatorclaimLicence>;
+  const certificateProgram = anchor.workspace.CreatorclaimCertificate as Program<CreatorclaimCertificate>;
   const buyer = provider.wallet as anchor.Wallet;
   const treasury = anchor.web3.Keypair.generate(); // Platform treasury wallet
+  const creator = anchor.web3.Keypair.generate(); // Certificate authority and royalty beneficiary
+  const bandMember = anchor.web3.Keypair.generate(); // Second royalty beneficiary
 
-  // Mocks/Placeholders
-  const certificateDetailsKP = anchor.web3.Keypair.generate(); // Mock CertificateDetails account pubkey
-  const certificateDetailsPubkey = certificateDetailsKP.publicKey;
+  // Signs the certificate program's licence-count hooks on behalf of the licence program
+  const [licenceAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("licence_authority")],
+    program.programId
+  );
+  // Accepted payment mints, platform fee and admins live in the certificate program
+  const [platformConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("platform_config")],
+    certificateProgram.programId
+  );
 
+  let certificateDetailsPubkey: web3.PublicKey = null;
   let paymentMint: web3.PublicKey = null;
   let buyerTokenAccount: web3.PublicKey = null;
-  let treasuryTokenAccount: web3.PublicKey = null;
+  let treasuryTokenAccount: web3.PublicKey = null; // Treasury PDA collecting platform fees
+  let treasuryWalletTokenAccount: web3.PublicKey = null; // Where admins withdraw fees to
+  let creatorTokenAccount: web3.PublicKey = null;
+  let bandMemberTokenAccount: web3.PublicKey = null;
   let licencePDA: web3.PublicKey = null;
   let licenceBump: number = 0;
+  let configParams = null; // PlatformConfig the suite runs under
+  const assetIds = new Map<string, web3.PublicKey>(); // CertificateDetails PDA -> asset key
 
   const purchasePrice = new BN(100 * 10**6); // Example: 100 USDC (assuming 6 decimals)
 
+  // Licence templates the suite registers certificates against
+  const STANDARD_TEMPLATE = 1; // Perpetual, transferable with a 10% resale royalty, sublicensable
+  const SHORT_TEMPLATE = 2; // Two-second term, neither transferable nor sublicensable
+  const RETIRED_TEMPLATE = 3; // Inactive; no longer sold
+  const EXCLUSIVE_TEMPLATE = 4; // Perpetual exclusive buy-out
+  const EXCLUSIVE_SHORT_TEMPLATE = 5; // Two-second exclusive term
+
+  const templatePda = (templateId: number): web3.PublicKey => {
+    const id = Buffer.alloc(2);
+    id.writeUInt16LE(templateId);
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("licence_template"), id],
+      program.programId
+    )[0];
+  };
+
+  const airdrop = async (to: web3.PublicKey, sol: number) => {
+    const signature = await connection.requestAirdrop(to, sol * web3.LAMPORTS_PER_SOL);
+    await connection.confirmTransaction(signature, "confirmed");
+  };
+
+  // Registers a certificate for a fresh asset key, priced in `mint` and splitting
+  // royalties 70/30 between `creator` and `bandMember`. Returns the CertificateDetails PDA.
+  const registerCertificate = async (
+    templateId: number = STANDARD_TEMPLATE,
+    mint: web3.PublicKey = paymentMint
+  ): Promise<web3.PublicKey> => {
+    const assetId = anchor.web3.Keypair.generate().publicKey;
+    const [certificateDetails] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("certificate_details"), assetId.toBuffer()],
+      certificateProgram.programId
+    );
+    const [assetRegistration] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("asset_registration"), assetId.toBuffer()],
+      certificateProgram.programId
+    );
+    await certificateProgram.methods
+      .registerCertificate(generateRandomHash(), templateId, purchasePrice, mint, [
+        { beneficiary: creator.publicKey, shareBps: 7000 },
+        { beneficiary: bandMember.publicKey, shareBps: 3000 },
+      ], { unverified: {} })
+      .accounts({
+        creator: creator.publicKey,
+        certificateDetails,
+        assetRegistration,
+        assetIdOrMintPk: assetId,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+    assetIds.set(certificateDetails.toBase58(), assetId);
+    return certificateDetails;
+  };
+
+  const setEscrowPeriod = async (secs: number) => {
+    configParams = { ...configParams, escrowPeriod: new BN(secs) };
+    await certificateProgram.methods
+      .updateConfig(configParams)
+      .accounts({ admin: buyer.publicKey, platformConfig })
+      .rpc();
+  };
+
+  const escrowPda = (licence: web3.PublicKey): web3.PublicKey =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), licence.toBuffer()],
+      program.programId
+    )[0];
+
+  const balanceOf = async (tokenAccount: web3.PublicKey): Promise<number> =>
+    Number((await getAccount(connection, tokenAccount)).amount);
+
+  // Royalty recipients' token accounts, in the same order as the certificate's splits
+  const splitAccounts = () => [
+    { pubkey: creatorTokenAccount, isWritable: true, isSigner: false },
+    { pubkey: bandMemberTokenAccount, isWritable: true, isSigner: false },
+  ];
+
+  const licencePda = (certificateDetails: web3.PublicKey, holder: web3.PublicKey): web3.PublicKey =>
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("licence"), certificateDetails.toBuffer(), holder.toBuffer()],
+      program.programId
+    )[0];
+
+  // Funds a fresh buyer with SOL for fees and `tokens` of the payment mint
+  const newBuyer = async (tokens: BN = purchasePrice) => {
+    const kp = anchor.web3.Keypair.generate();
+    await airdrop(kp.publicKey, 0.5);
+    const tokenAccount = await createAccount(connection, kp, paymentMint, kp.publicKey);
+    if (!tokens.isZero()) {
+      await mintTo(connection, buyer.payer, paymentMint, tokenAccount, buyer.publicKey, tokens.toNumber());
+    }
+    return { kp, tokenAccount };
+  };
+
+  // Buys a licence on `certificateDetails` into escrow. Returns the Licence PDA.
+  const purchaseEscrowed = async (
+    buyerKP: web3.Keypair,
+    buyerTokenAccount: web3.PublicKey,
+    certificateDetails: web3.PublicKey
+  ): Promise<web3.PublicKey> => {
+    const licence = licencePda(certificateDetails, buyerKP.publicKey);
+    await program.methods
+      .purchaseLicenceEscrowed(purchasePrice)
+      .accounts({
+        buyer: buyerKP.publicKey,
+        buyerTokenAccount,
+        licence,
+        escrowTokenAccount: escrowPda(licence),
+        certificateDetails,
+        licenceTemplate: templatePda(STANDARD_TEMPLATE),
+        paymentMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+      })
+      .signers([buyerKP])
+      .rpc();
+    return licence;
+  };
+
+  const releaseEscrow = (licence: web3.PublicKey, holder: web3.PublicKey, certificateDetails: web3.PublicKey) =>
+    program.methods
+      .releaseEscrow()
+      .accounts({
+        licence,
+        buyer: holder,
+        certificateDetails,
+        platformConfig,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        paymentMint,
+        escrowTokenAccount: escrowPda(licence),
+        treasuryTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(splitAccounts())
+      .rpc();
+
+  const retireCertificate = (certificateDetails: web3.PublicKey) =>
+    certificateProgram.methods
+      .retireCertificate()
+      .accounts({
+        authority: creator.publicKey,
+        certificateDetails,
+        assetIdOrMintPk: assetIds.get(certificateDetails.toBase58()),
+      })
+      .signers([creator])
+      .rpc();
+
+  const renew = (
+    holderKP: web3.Keypair,
+    holderTokenAccount: web3.PublicKey,
+    licence: web3.PublicKey,
+    certificateDetails: web3.PublicKey,
+    templateId: number,
+    maxPrice: BN = purchasePrice
+  ) =>
+    program.methods
+      .renewLicence(maxPrice)
+      .accounts({
+        holder: holderKP.publicKey,
+        holderTokenAccount,
+        licence,
+        certificateDetails,
+        licenceTemplate: templatePda(templateId),
+        paymentMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+        treasuryTokenAccount,
+      })
+      .remainingAccounts(splitAccounts())
+      .signers([holderKP])
+      .rpc();
+
+  // Issues a sublicence of `parentLicence` to a fresh wallet. Returns the sublicence PDA.
+  const issueSublicence = async (
+    holderKP: web3.Keypair,
+    parentLicence: web3.PublicKey,
+    certificateDetails: web3.PublicKey,
+    templateId: number = STANDARD_TEMPLATE
+  ): Promise<web3.PublicKey> => {
+    const sublicensee = anchor.web3.Keypair.generate().publicKey;
+    const [sublicence] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("sublicence"), parentLicence.toBuffer(), sublicensee.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .issueSublicence(sublicensee)
+      .accounts({
+        holder: holderKP.publicKey,
+        parentLicence,
+        certificateDetails,
+        licenceTemplate: templatePda(templateId),
+        sublicence,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([holderKP])
+      .rpc();
+    return sublicence;
+  };
+
+  // Buys a licence on `certificateDetails` through purchase_licence. Returns the Licence PDA.
+  const purchase = async (
+    buyerKP: web3.Keypair,
+    buyerTokenAccount: web3.PublicKey,
+    certificateDetails: web3.PublicKey,
+    templateId: number = STANDARD_TEMPLATE,
+    maxPrice: BN = purchasePrice
+  ): Promise<web3.PublicKey> => {
+    const licence = licencePda(certificateDetails, buyerKP.publicKey);
+    await program.methods
+      .purchaseLicence(maxPrice)
+      .accounts({
+        buyer: buyerKP.publicKey,
+        buyerTokenAccount,
+        licence,
+        certificateDetails,
+        licenceTemplate: templatePda(templateId),
+        paymentMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+        treasuryTokenAccount,
+      })
+      .remainingAccounts(splitAccounts())
+      .signers([buyerKP])
+      .rpc();
+    return licence;
+  };
+
   // Setup before tests
   before(async () => {
-    // 1. Airdrop SOL to treasury (to create token account)
-    await connection.requestAirdrop(treasury.publicKey, 1 * web3.LAMPORTS_PER_SOL);
-    await delay(500); // Delay to allow airdrop confirmation
+    // 1. Airdrop SOL to treasury (to create token account) and to the royalty beneficiaries
+    await airdrop(treasury.publicKey, 1);
+    await airdrop(creator.publicKey, 1);
+    await airdrop(bandMember.publicKey, 1); // Receives SOL royalties, so must stay rent-exempt
 
     // 2. Create Payment Mint (mock USDC)
     paymentMint = await createMint(
@@ -62,14 +332,18 @@ describe("creatorclaim_licence", () => {
     );
     console.log(`Buyer Token Account: ${buyerTokenAccount.toBase58()}`);
 
-    // 4. Create Treasury Token Account
-    treasuryTokenAccount = await createAccount(
+    // 4. Create the treasury wallet's Token Account (fee withdrawal destination)
+    treasuryWalletTokenAccount = await createAccount(
         connection,
         treasury, // Treasury pays for its own account
         paymentMint,
         treasury.publicKey // Owner of the account
     );
-    console.log(`Treasury Token Account: ${treasuryTokenAccount.toBase58()}`);
+    console.log(`Treasury Wallet Token Account: ${treasuryWalletTokenAccount.toBase58()}`);
+
+    // 4b. Create the royalty beneficiaries' token accounts
+    creatorTokenAccount = await createAccount(connection, buyer.payer, paymentMint, creator.publicKey);
+    bandMemberTokenAccount = await createAccount(connection, buyer.payer, paymentMint, bandMember.publicKey);
 
     // 5. Mint tokens to Buyer
     const mintAmount = purchasePrice.mul(new BN(2)); // Mint 2x the price
@@ -86,7 +360,81 @@ describe("creatorclaim_licence", () => {
     const buyerAccountInfo = await getAccount(connection, buyerTokenAccount);
     assert.equal(buyerAccountInfo.amount.toString(), mintAmount.toString(), "Buyer account balance mismatch after mint");
 
-    // 6. Derive Licence PDA (needed for revoke test later)
+    // 6. Accept the payment mint on the platform, initializing the config if this suite runs alone
+    configParams = {
+      admins: [buyer.publicKey],
+      treasury: treasury.publicKey,
+      feeBps: 250,
+      acceptedPaymentMints: [paymentMint],
+      licenceProgram: program.programId,
+      escrowPeriod: new BN(0),
+    };
+    if ((await certificateProgram.account.platformConfig.fetchNullable(platformConfig)) === null) {
+      const [programData] = anchor.web3.PublicKey.findProgramAddressSync(
+        [certificateProgram.programId.toBuffer()],
+        new anchor.web3.PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+      await certificateProgram.methods
+        .initializeConfig(configParams)
+        .accounts({
+          upgradeAuthority: buyer.publicKey,
+          platformConfig,
+          program: certificateProgram.programId,
+          programData,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+    }
+    await certificateProgram.methods
+      .updateConfig(configParams)
+      .accounts({ admin: buyer.publicKey, platformConfig })
+      .rpc();
+
+    // 6b. Create the Treasury PDA that collects platform fees in this mint
+    [treasuryTokenAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), paymentMint.toBuffer()],
+        program.programId
+    );
+    await program.methods
+      .initializeTreasury()
+      .accounts({
+        admin: buyer.publicKey,
+        platformConfig,
+        paymentMint,
+        treasuryTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .rpc();
+    console.log(`Treasury PDA: ${treasuryTokenAccount.toBase58()}`);
+
+    // 6c. Create (or reset) the licence templates
+    const templates: [number, any][] = [
+      [STANDARD_TEMPLATE, { termSecs: null, exclusive: false, sublicensable: true, region: null, transferable: true, resaleRoyaltyBps: 1000, active: true }],
+      [SHORT_TEMPLATE, { termSecs: new BN(2), exclusive: false, sublicensable: false, region: null, transferable: false, resaleRoyaltyBps: 0, active: true }],
+      [RETIRED_TEMPLATE, { termSecs: null, exclusive: false, sublicensable: false, region: null, transferable: false, resaleRoyaltyBps: 0, active: false }],
+      [EXCLUSIVE_TEMPLATE, { termSecs: null, exclusive: true, sublicensable: false, region: null, transferable: false, resaleRoyaltyBps: 0, active: true }],
+      [EXCLUSIVE_SHORT_TEMPLATE, { termSecs: new BN(2), exclusive: true, sublicensable: false, region: null, transferable: false, resaleRoyaltyBps: 0, active: true }],
+    ];
+    for (const [templateId, params] of templates) {
+      const licenceTemplate = templatePda(templateId);
+      if ((await program.account.licenceTemplate.fetchNullable(licenceTemplate)) === null) {
+        await program.methods
+          .createLicenceTemplate(templateId, params)
+          .accounts({ admin: buyer.publicKey, platformConfig, licenceTemplate, systemProgram: anchor.web3.SystemProgram.programId })
+          .rpc();
+      } else {
+        await program.methods
+          .updateLicenceTemplate(params)
+          .accounts({ admin: buyer.publicKey, platformConfig, licenceTemplate })
+          .rpc();
+      }
+    }
+
+    // 7. Register the certificate being licensed through the certificate program
+    certificateDetailsPubkey = await registerCertificate();
+
+    // 8. Derive Licence PDA (needed for revoke test later)
     const [pda, bump] = anchor.web3.PublicKey.findProgramAddressSync(
         [
             Buffer.from("licence"),
@@ -100,24 +448,29 @@ describe("creatorclaim_licence", () => {
   });
 
   it("Purchases a licence successfully!", async () => {
-    const expiryTimestamp = null; // Perpetual licence
-
     const buyerBalanceBefore = (await getAccount(connection, buyerTokenAccount)).amount;
     const treasuryBalanceBefore = (await getAccount(connection, treasuryTokenAccount)).amount;
+    const creatorBalanceBefore = (await getAccount(connection, creatorTokenAccount)).amount;
+    const bandMemberBalanceBefore = (await getAccount(connection, bandMemberTokenAccount)).amount;
 
     // Call the purchase_licence instruction
     const tx = await program.methods
-      .purchaseLicence(purchasePrice, expiryTimestamp)
+      .purchaseLicence(purchasePrice)
       .accounts({
         buyer: buyer.publicKey,
         buyerTokenAccount: buyerTokenAccount,
         licence: licencePDA,
         certificateDetails: certificateDetailsPubkey,
+        licenceTemplate: templatePda(STANDARD_TEMPLATE),
         treasuryTokenAccount: treasuryTokenAccount,
         paymentMint: paymentMint,
         tokenProgram: TOKEN_PROGRAM_ID,
         systemProgram: anchor.web3.SystemProgram.programId,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
       })
+      .remainingAccounts(splitAccounts())
       .rpc();
 
     console.log("Purchase licence transaction signature", tx);
@@ -131,38 +484,81 @@ describe("creatorclaim_licence", () => {
     assert.ok(licenceAccountData.buyer.equals(buyer.publicKey));
     assert.ok(licenceAccountData.purchasePrice.eq(purchasePrice));
     assert.ok(licenceAccountData.purchaseTimestamp.toNumber() > 0);
-    assert.isNull(licenceAccountData.expiryTimestamp);
+    assert.isNull(licenceAccountData.expiryTimestamp); // The standard template is perpetual
+    assert.equal(licenceAccountData.licenceTemplateId, STANDARD_TEMPLATE);
     assert.equal(JSON.stringify(licenceAccountData.status), JSON.stringify({ active: {} })); // Check enum serialization
     assert.equal(licenceAccountData.bump, licenceBump);
 
+    // The purchase is counted on the certificate through the licence-count hook
+    const certificateData = await certificateProgram.account.certificateDetails.fetch(certificateDetailsPubkey);
+    assert.equal(certificateData.activeLicences, 1);
+
     // Assertions on Token Balances
     const buyerBalanceAfter = (await getAccount(connection, buyerTokenAccount)).amount;
     const treasuryBalanceAfter = (await getAccount(connection, treasuryTokenAccount)).amount;
+    const creatorBalanceAfter = (await getAccount(connection, creatorTokenAccount)).amount;
+    const bandMemberBalanceAfter = (await getAccount(connection, bandMemberTokenAccount)).amount;
 
+    // 2.5% platform fee, then the remaining 97.5 tokens split 70/30
+    const platformFee = new BN(2_500_000);
     const expectedBuyerBalance = new BN(buyerBalanceBefore.toString()).sub(purchasePrice);
-    const expectedTreasuryBalance = new BN(treasuryBalanceBefore.toString()).add(purchasePrice);
+    const expectedTreasuryBalance = new BN(treasuryBalanceBefore.toString()).add(platformFee);
+    const expectedCreatorBalance = new BN(creatorBalanceBefore.toString()).add(new BN(68_250_000));
+    const expectedBandMemberBalance = new BN(bandMemberBalanceBefore.toString()).add(new BN(29_250_000));
 
     assert.equal(buyerBalanceAfter.toString(), expectedBuyerBalance.toString(), "Buyer balance incorrect after purchase");
     assert.equal(treasuryBalanceAfter.toString(), expectedTreasuryBalance.toString(), "Treasury balance incorrect after purchase");
+    assert.equal(creatorBalanceAfter.toString(), expectedCreatorBalance.toString(), "Creator royalty incorrect after purchase");
+    assert.equal(bandMemberBalanceAfter.toString(), expectedBandMemberBalance.toString(), "Band member royalty incorrect after purchase");
 
     console.log("Licence purchased successfully. PDA:", licenceAccountData);
   });
 
+  it("Withdraws accumulated platform fees to the treasury wallet", async () => {
+    const feesHeld = Number((await getAccount(connection, treasuryTokenAccount)).amount);
+    assert.isAbove(feesHeld, 0, "Treasury should hold the purchase fee");
+    const walletBalanceBefore = Number((await getAccount(connection, treasuryWalletTokenAccount)).amount);
+
+    const withdrawAccounts = {
+      admin: buyer.publicKey,
+      platformConfig,
+      paymentMint,
+      treasuryTokenAccount,
+      destinationTokenAccount: treasuryWalletTokenAccount,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    await program.methods.withdrawFees().accounts(withdrawAccounts).rpc();
+
+    const walletBalanceAfter = Number((await getAccount(connection, treasuryWalletTokenAccount)).amount);
+    assert.equal(walletBalanceAfter - walletBalanceBefore, feesHeld);
+    assert.equal((await getAccount(connection, treasuryTokenAccount)).amount.toString(), "0");
+
+    // Nothing left to sweep
+    try {
+      await program.methods.withdrawFees().accounts(withdrawAccounts).rpc();
+      assert.fail("Withdrawal should have failed on an empty treasury.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ZeroBalance");
+    }
+  });
+
   it("Revokes a licence successfully!", async () => {
     // Ensure licence was created in the previous test
     const licenceAccountDataBefore = await program.account.licence.fetch(licencePDA);
     assert.equal(JSON.stringify(licenceAccountDataBefore.status), JSON.stringify({ active: {} }), "Licence not active before revoke");
 
-    // Call the revoke_licence instruction
-    // Using buyer as revoker because auth check is placeholder
+    // Call the revoke_licence instruction as the certificate authority
     const tx = await program.methods
-      .revokeLicence()
+      .revokeLicence(0)
       .accounts({
-        revoker: buyer.publicKey,
+        revoker: creator.publicKey,
         licence: licencePDA,
         certificateDetails: certificateDetailsPubkey,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
       })
-      .signers([buyer.payer]) // Signer is the wallet provider
+      .signers([creator])
       .rpc();
 
     console.log("Revoke licence transaction signature", tx);
@@ -174,6 +570,10 @@ describe("creatorclaim_licence", () => {
     // Assertions on Licence PDA status
     assert.equal(JSON.stringify(licenceAccountDataAfter.status), JSON.stringify({ revoked: {} }), "Licence status not Revoked after revoke");
 
+    // The revoked licence no longer counts as active
+    const certificateData = await certificateProgram.account.certificateDetails.fetch(certificateDetailsPubkey);
+    assert.equal(certificateData.activeLicences, 0);
+
     console.log("Licence revoked successfully. New status:", licenceAccountDataAfter.status);
   });
 
@@ -182,9 +582,7 @@ describe("creatorclaim_licence", () => {
   it("Should fail purchase with insufficient funds", async () => {
     // Create a new buyer with no tokens
     const poorBuyerKP = anchor.web3.Keypair.generate();
-    await connection.requestAirdrop(poorBuyerKP.publicKey, 0.1 * web3.LAMPORTS_PER_SOL); // Airdrop SOL for tx fees
-    await delay(500);
-    const poorBuyerWallet = new anchor.Wallet(poorBuyerKP);
+    await airdrop(poorBuyerKP.publicKey, 0.1); // Airdrop SOL for tx fees
 
     const poorBuyerTokenAccount = await createAccount(
         connection,
@@ -206,17 +604,22 @@ describe("creatorclaim_licence", () => {
 
     try {
       await program.methods
-        .purchaseLicence(purchasePrice, null)
+        .purchaseLicence(purchasePrice)
         .accounts({
             buyer: poorBuyerKP.publicKey,
             buyerTokenAccount: poorBuyerTokenAccount,
             licence: poorBuyerLicencePDA,
             certificateDetails: certificateDetailsPubkey,
+            licenceTemplate: templatePda(STANDARD_TEMPLATE),
             treasuryTokenAccount: treasuryTokenAccount,
             paymentMint: paymentMint,
             tokenProgram: TOKEN_PROGRAM_ID,
             systemProgram: anchor.web3.SystemProgram.programId,
+            licenceAuthority,
+            certificateProgram: certificateProgram.programId,
+            platformConfig,
         })
+        .remainingAccounts(splitAccounts())
         .signers([poorBuyerKP]) // Sign with the poor buyer's keypair
         .rpc();
       assert.fail("Purchase should have failed due to insufficient funds.");
@@ -239,13 +642,16 @@ describe("creatorclaim_licence", () => {
 
       try {
           await program.methods
-              .revokeLicence()
+              .revokeLicence(0)
               .accounts({
-                  revoker: buyer.publicKey, // Still using buyer as placeholder revoker
+                  revoker: creator.publicKey,
                   licence: licencePDA,
                   certificateDetails: certificateDetailsPubkey,
+                  licenceAuthority,
+                  certificateProgram: certificateProgram.programId,
+                  platformConfig,
               })
-              .signers([buyer.payer])
+              .signers([creator])
               .rpc();
           assert.fail("Revoke should have failed as licence is already revoked.");
       } catch (err) {
@@ -260,24 +666,23 @@ describe("creatorclaim_licence", () => {
   it("Should fail revoke with wrong certificate_details account", async () => {
       // We need an active licence first. Let's create a new one.
       const testBuyerKP = anchor.web3.Keypair.generate();
-      await connection.requestAirdrop(testBuyerKP.publicKey, 0.5 * web3.LAMPORTS_PER_SOL);
-      await delay(500);
-      const testBuyerWallet = new anchor.Wallet(testBuyerKP);
-      const testCertDetailsKP = anchor.web3.Keypair.generate(); // Correct cert details for this new licence
-      const wrongCertDetailsKP = anchor.web3.Keypair.generate(); // Incorrect cert details
+      await airdrop(testBuyerKP.publicKey, 0.5);
+      const testCertDetails = await registerCertificate(); // Correct cert details for this new licence
+      const wrongCertDetails = await registerCertificate(); // Incorrect cert details
 
       const testBuyerTokenAccount = await createAccount(connection, testBuyerKP, paymentMint, testBuyerKP.publicKey);
       await mintTo(connection, buyer.payer, paymentMint, testBuyerTokenAccount, buyer.publicKey, purchasePrice.toNumber());
 
       const [newLicencePDA, __] = anchor.web3.PublicKey.findProgramAddressSync(
-          [Buffer.from("licence"), testCertDetailsKP.publicKey.toBuffer(), testBuyerKP.publicKey.toBuffer()],
+          [Buffer.from("licence"), testCertDetails.toBuffer(), testBuyerKP.publicKey.toBuffer()],
           program.programId
       );
 
       // Purchase the new licence
       await program.methods
-          .purchaseLicence(purchasePrice, null)
-          .accounts({ buyer: testBuyerKP.publicKey, buyerTokenAccount: testBuyerTokenAccount, licence: newLicencePDA, certificateDetails: testCertDetailsKP.publicKey, treasuryTokenAccount, paymentMint, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: anchor.web3.SystemProgram.programId })
+          .purchaseLicence(purchasePrice)
+          .accounts({ buyer: testBuyerKP.publicKey, buyerTokenAccount: testBuyerTokenAccount, licence: newLicencePDA, certificateDetails: testCertDetails, licenceTemplate: templatePda(STANDARD_TEMPLATE), treasuryTokenAccount, paymentMint, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: anchor.web3.SystemProgram.programId, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+          .remainingAccounts(splitAccounts())
           .signers([testBuyerKP])
           .rpc();
       console.log("Created a new licence for the constraint test.");
@@ -286,25 +691,871 @@ describe("creatorclaim_licence", () => {
       // Attempt revoke with the WRONG certificate_details account
       try {
           await program.methods
-              .revokeLicence()
+              .revokeLicence(0)
               .accounts({
-                  revoker: testBuyerKP.publicKey, // Placeholder revoker
+                  revoker: creator.publicKey,
                   licence: newLicencePDA,
-                  certificateDetails: wrongCertDetailsKP.publicKey, // Passing wrong account
+                  certificateDetails: wrongCertDetails, // Passing wrong account
+                  licenceAuthority,
+                  certificateProgram: certificateProgram.programId,
+                  platformConfig,
               })
-              .signers([testBuyerKP])
+              .signers([creator])
               .rpc();
           assert.fail("Revoke should have failed due to certificate mismatch constraint.");
       } catch (err) {
-          // Anchor wraps constraint errors differently
-          // console.error("Constraint error:", JSON.stringify(err, null, 2));
-          assert.include(err.toString(), "AnchorError: AccountNotInitialized", "Error should indicate constraint failure (AccountNotInitialized for derived constraint)");
-          // Or specifically check for the custom error code if Anchor surfaces it clearly
-          // assert.equal(err.error.errorCode.code, "CertificateMismatch");
+          assert.equal(err.error.errorCode.code, "CertificateMismatch");
           console.log("Successfully caught error for wrong certificate_details constraint.");
       }
   });
 
-  // - revoke_licence: unauthorized revoker (once auth logic is added)
+  // - purchase_licence: royalty recipients out of order
+  it("Should fail purchase when split recipients do not match the royalty splits", async () => {
+    const testBuyerKP = anchor.web3.Keypair.generate();
+    await airdrop(testBuyerKP.publicKey, 0.5);
+    const testBuyerTokenAccount = await createAccount(connection, testBuyerKP, paymentMint, testBuyerKP.publicKey);
+    await mintTo(connection, buyer.payer, paymentMint, testBuyerTokenAccount, buyer.publicKey, purchasePrice.toNumber());
+
+    const [testLicencePDA] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("licence"), certificateDetailsPubkey.toBuffer(), testBuyerKP.publicKey.toBuffer()],
+        program.programId
+    );
+
+    try {
+      await program.methods
+        .purchaseLicence(purchasePrice)
+        .accounts({ buyer: testBuyerKP.publicKey, buyerTokenAccount: testBuyerTokenAccount, licence: testLicencePDA, certificateDetails: certificateDetailsPubkey, licenceTemplate: templatePda(STANDARD_TEMPLATE), treasuryTokenAccount, paymentMint, tokenProgram: TOKEN_PROGRAM_ID, systemProgram: anchor.web3.SystemProgram.programId, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+        .remainingAccounts(splitAccounts().reverse()) // Band member first, creator second
+        .signers([testBuyerKP])
+        .rpc();
+      assert.fail("Purchase should have failed for swapped split recipients.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "SplitMismatch");
+      console.log("Successfully caught error for mismatched split recipients.");
+    }
+  });
+
+  // - purchase_licence: the template sets the term
+  it("Derives the licence expiry from the certificate's template", async () => {
+    const shortCertDetails = await registerCertificate(SHORT_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer();
+    const licence = await purchase(kp, tokenAccount, shortCertDetails, SHORT_TEMPLATE);
+
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.equal(licenceData.licenceTemplateId, SHORT_TEMPLATE);
+    assert.equal(licenceData.expiryTimestamp.toNumber(), licenceData.purchaseTimestamp.toNumber() + 2);
+  });
+
+  // - purchase_licence: inactive template
+  it("Should fail purchase under an inactive template", async () => {
+    const retiredCertDetails = await registerCertificate(RETIRED_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer();
+
+    try {
+      await purchase(kp, tokenAccount, retiredCertDetails, RETIRED_TEMPLATE);
+      assert.fail("Purchase should have failed for an inactive template.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ExpiredTemplate");
+    }
+  });
+
+  // - create_licence_template: zero-length term
+  it("Should fail to create a template with a non-positive term", async () => {
+    try {
+      await program.methods
+        .createLicenceTemplate(99, { termSecs: new BN(0), exclusive: false, sublicensable: false, region: null, transferable: false, resaleRoyaltyBps: 0, active: true })
+        .accounts({ admin: buyer.publicKey, platformConfig, licenceTemplate: templatePda(99), systemProgram: anchor.web3.SystemProgram.programId })
+        .rpc();
+      assert.fail("Template creation should have failed for a zero term.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "InvalidTemplateTerm");
+    }
+  });
+
+  // - revoke_licence: unauthorized revoker
+  it("Should fail revoke by a signer who is neither the certificate authority nor an admin", async () => {
+      const strangerKP = anchor.web3.Keypair.generate();
+      await airdrop(strangerKP.publicKey, 0.1);
+
+      try {
+          await program.methods
+              .revokeLicence(0)
+              .accounts({
+                  revoker: strangerKP.publicKey,
+                  licence: licencePDA,
+                  certificateDetails: certificateDetailsPubkey,
+                  licenceAuthority,
+                  certificateProgram: certificateProgram.programId,
+                  platformConfig,
+              })
+              .signers([strangerKP])
+              .rpc();
+          assert.fail("Revoke should have failed for an unauthorized revoker.");
+      } catch (err) {
+          assert.equal(err.error.errorCode.code, "UnauthorizedRevoker");
+          console.log("Successfully caught error for an unauthorized revoker.");
+      }
+  });
+
+  // - revoke_licence: partial refund funded by the revoker
+  it("Refunds part of the purchase price when revoking", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer();
+    const licence = await purchase(kp, tokenAccount, certDetails);
+    assert.equal(await balanceOf(tokenAccount), 0);
+    const creatorBefore = await balanceOf(creatorTokenAccount);
+
+    const revokeAccounts = {
+      revoker: creator.publicKey,
+      licence,
+      certificateDetails: certDetails,
+      licenceAuthority,
+      certificateProgram: certificateProgram.programId,
+      platformConfig,
+      revokerTokenAccount: creatorTokenAccount,
+      buyerTokenAccount: tokenAccount,
+      paymentMint,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+
+    try {
+      await program.methods.revokeLicence(10001).accounts(revokeAccounts).signers([creator]).rpc();
+      assert.fail("Revoke should have failed for a refund above 100%.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "InvalidRefundBps");
+    }
+
+    await program.methods.revokeLicence(5000).accounts(revokeAccounts).signers([creator]).rpc();
+
+    const refund = purchasePrice.toNumber() / 2;
+    assert.equal(await balanceOf(tokenAccount), refund);
+    assert.equal(creatorBefore - await balanceOf(creatorTokenAccount), refund);
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.equal(JSON.stringify(licenceData.status), JSON.stringify({ revoked: {} }));
+  });
+
+  // - expire_licence: time-limited licence past its term
+  it("Expires a time-limited licence and closes it once ended", async () => {
+    const certDetails = await registerCertificate(SHORT_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer();
+    const licence = await purchase(kp, tokenAccount, certDetails, SHORT_TEMPLATE);
+    const expireAccounts = {
+      licence,
+      buyer: kp.publicKey,
+      certificateDetails: certDetails,
+      licenceAuthority,
+      certificateProgram: certificateProgram.programId,
+      platformConfig,
+    };
+
+    try {
+      await program.methods.expireLicence(false).accounts(expireAccounts).rpc();
+      assert.fail("Expire should have failed before the term ends.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "LicenceNotExpired");
+    }
+
+    await delay(3500);
+    await program.methods.expireLicence(false).accounts(expireAccounts).rpc();
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.equal(JSON.stringify(licenceData.status), JSON.stringify({ expired: {} }));
+    assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 0);
+
+    // An ended licence can only be passed again to close it
+    try {
+      await program.methods.expireLicence(false).accounts(expireAccounts).rpc();
+      assert.fail("Expire should have failed for an already expired licence.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "LicenceExpired");
+    }
+
+    await program.methods.expireLicence(true).accounts(expireAccounts).rpc();
+    assert.isNull(await program.account.licence.fetchNullable(licence));
+    assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 0);
+  });
+
+  // - renew_licence: extend a time-limited licence, before and after it expires
+  it("Renews a time-limited licence and reactivates it after expiry", async () => {
+    const certDetails = await registerCertificate(SHORT_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer(purchasePrice.mul(new BN(3)));
+    const licence = await purchase(kp, tokenAccount, certDetails, SHORT_TEMPLATE);
+    const firstExpiry = (await program.account.licence.fetch(licence)).expiryTimestamp.toNumber();
+
+    await renew(kp, tokenAccount, licence, certDetails, SHORT_TEMPLATE);
+    let licenceData = await program.account.licence.fetch(licence);
+    assert.isAtLeast(licenceData.expiryTimestamp.toNumber(), firstExpiry + 2);
+    assert.equal(licenceData.renewalCount, 1);
+
+    await delay(6000);
+    await program.methods
+      .expireLicence(false)
+      .accounts({ licence, buyer: kp.publicKey, certificateDetails: certDetails, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+      .rpc();
+    assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 0);
+
+    // Renewing an expired licence brings it back into the active count
+    await renew(kp, tokenAccount, licence, certDetails, SHORT_TEMPLATE);
+    licenceData = await program.account.licence.fetch(licence);
+    assert.equal(JSON.stringify(licenceData.status), JSON.stringify({ active: {} }));
+    assert.equal(licenceData.renewalCount, 2);
+    assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 1);
+  });
+
+  // - renew_licence: perpetual licences have nothing to renew
+  it("Should fail to renew a perpetual licence", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer(purchasePrice.mul(new BN(2)));
+    const licence = await purchase(kp, tokenAccount, certDetails);
+
+    try {
+      await renew(kp, tokenAccount, licence, certDetails, STANDARD_TEMPLATE);
+      assert.fail("Renew should have failed for a perpetual licence.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "PerpetualLicence");
+    }
+  });
+
+  // - transfer_licence: hand a licence on, paying the resale royalty
+  it("Transfers a licence to a new holder and pays the resale royalty", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer(purchasePrice.mul(new BN(2)));
+    const licence = await purchase(kp, tokenAccount, certDetails);
+    const newHolder = anchor.web3.Keypair.generate().publicKey;
+    const creatorBefore = await balanceOf(creatorTokenAccount);
+    const bandMemberBefore = await balanceOf(bandMemberTokenAccount);
+
+    await program.methods
+      .transferLicence(newHolder)
+      .accounts({
+        holder: kp.publicKey,
+        licence,
+        certificateDetails: certDetails,
+        licenceTemplate: templatePda(STANDARD_TEMPLATE),
+        holderTokenAccount: tokenAccount,
+        paymentMint,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts(splitAccounts())
+      .signers([kp])
+      .rpc();
+
+    // 10% of the purchase price, split 70/30 with no platform fee
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.ok(licenceData.holder.equals(newHolder));
+    assert.ok(licenceData.buyer.equals(kp.publicKey));
+    assert.equal(await balanceOf(creatorTokenAccount) - creatorBefore, 7_000_000);
+    assert.equal(await balanceOf(bandMemberTokenAccount) - bandMemberBefore, 3_000_000);
+  });
+
+  // - transfer_licence: template forbids transfers
+  it("Should fail to transfer a licence under a non-transferable template", async () => {
+    const certDetails = await registerCertificate(SHORT_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer();
+    const licence = await purchase(kp, tokenAccount, certDetails, SHORT_TEMPLATE);
+
+    try {
+      await program.methods
+        .transferLicence(anchor.web3.Keypair.generate().publicKey)
+        .accounts({ holder: kp.publicKey, licence, certificateDetails: certDetails, licenceTemplate: templatePda(SHORT_TEMPLATE) })
+        .signers([kp])
+        .rpc();
+      assert.fail("Transfer should have failed for a non-transferable template.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotTransferable");
+    }
+  });
+
+  // - issue_sublicence / lapse_sublicence: sublicence follows its parent
+  it("Issues a sublicence that lapses when its parent is revoked", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer();
+    const parentLicence = await purchase(kp, tokenAccount, certDetails);
+    const sublicence = await issueSublicence(kp, parentLicence, certDetails);
+
+    let sublicenceData = await program.account.licence.fetch(sublicence);
+    assert.ok(sublicenceData.parent.equals(parentLicence));
+    assert.equal(JSON.stringify(sublicenceData.status), JSON.stringify({ active: {} }));
+    // Sublicences do not take a slot in the certificate's active count
+    assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 1);
+
+    try {
+      await program.methods.lapseSublicence().accounts({ sublicence, parentLicence }).rpc();
+      assert.fail("Lapse should have failed while the parent is active.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ParentLicenceActive");
+    }
+
+    await program.methods
+      .revokeLicence(0)
+      .accounts({ revoker: creator.publicKey, licence: parentLicence, certificateDetails: certDetails, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+      .signers([creator])
+      .rpc();
+    await program.methods.lapseSublicence().accounts({ sublicence, parentLicence }).rpc();
+
+    sublicenceData = await program.account.licence.fetch(sublicence);
+    assert.equal(JSON.stringify(sublicenceData.status), JSON.stringify({ revoked: {} }));
+  });
+
+  // - expire_licence: reclaim rent once the certificate has been closed, and return a
+  //   sublicence's rent to the holder who issued it
+  it("Closes ended licences after the certificate is closed, refunding whoever paid the rent", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer();
+    const parentLicence = await purchase(kp, tokenAccount, certDetails);
+    const sublicence = await issueSublicence(kp, parentLicence, certDetails);
+    assert.ok((await program.account.licence.fetch(sublicence)).buyer.equals(kp.publicKey));
+
+    await program.methods
+      .revokeLicence(0)
+      .accounts({ revoker: creator.publicKey, licence: parentLicence, certificateDetails: certDetails, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+      .signers([creator])
+      .rpc();
+    await program.methods.lapseSublicence().accounts({ sublicence, parentLicence }).rpc();
+    await retireCertificate(certDetails);
+    assert.isNull(await certificateProgram.account.certificateDetails.fetchNullable(certDetails));
+
+    const closeAccounts = (licence: web3.PublicKey) => ({
+      licence,
+      buyer: kp.publicKey,
+      certificateDetails: certDetails,
+      licenceAuthority,
+      certificateProgram: certificateProgram.programId,
+      platformConfig,
+    });
+    for (const licence of [parentLicence, sublicence]) {
+      const rent = (await connection.getAccountInfo(licence)).lamports;
+      const before = await connection.getBalance(kp.publicKey);
+      await program.methods.expireLicence(true).accounts(closeAccounts(licence)).rpc();
+      assert.isNull(await program.account.licence.fetchNullable(licence));
+      assert.equal(await connection.getBalance(kp.publicKey) - before, rent);
+    }
+  });
+
+  // - issue_sublicence: template forbids sublicensing
+  it("Should fail to sublicense under a non-sublicensable template", async () => {
+    const certDetails = await registerCertificate(SHORT_TEMPLATE);
+    const { kp, tokenAccount } = await newBuyer();
+    const parentLicence = await purchase(kp, tokenAccount, certDetails, SHORT_TEMPLATE);
+
+    try {
+      await issueSublicence(kp, parentLicence, certDetails, SHORT_TEMPLATE);
+      assert.fail("Sublicence should have failed for a non-sublicensable template.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "NotSublicensable");
+    }
+  });
+
+  // - issue_sublicence: no new rights on a retired work
+  it("Should fail to sublicense a licence on a retired certificate", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer();
+    const parentLicence = await purchase(kp, tokenAccount, certDetails);
+    await retireCertificate(certDetails); // Stays open while the licence is active
+
+    try {
+      await issueSublicence(kp, parentLicence, certDetails);
+      assert.fail("Sublicence should have failed on a retired certificate.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "CertificateRetired");
+    }
+  });
+
+  // - purchase_licence_with_sol / revoke_licence: licences paid in lamports
+  it("Purchases a licence in SOL and refunds it in lamports on revoke", async () => {
+    const certDetails = await registerCertificate();
+    const solBuyer = anchor.web3.Keypair.generate();
+    await airdrop(solBuyer.publicKey, 2);
+    const licence = licencePda(certDetails, solBuyer.publicKey);
+    const lamportPrice = new BN(web3.LAMPORTS_PER_SOL);
+    const solAccounts = {
+      buyer: solBuyer.publicKey,
+      licence,
+      certificateDetails: certDetails,
+      licenceTemplate: templatePda(STANDARD_TEMPLATE),
+      systemProgram: anchor.web3.SystemProgram.programId,
+      licenceAuthority,
+      certificateProgram: certificateProgram.programId,
+      platformConfig,
+      treasury: treasury.publicKey,
+    };
+    const beneficiaryWallets = [
+      { pubkey: creator.publicKey, isWritable: true, isSigner: false },
+      { pubkey: bandMember.publicKey, isWritable: true, isSigner: false },
+    ];
+
+    // The certificate only lists a token price so far
+    try {
+      await program.methods
+        .purchaseLicenceWithSol(lamportPrice)
+        .accounts(solAccounts)
+        .remainingAccounts(beneficiaryWallets)
+        .signers([solBuyer])
+        .rpc();
+      assert.fail("SOL purchase should have failed without a lamport price.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "LamportsNotAccepted");
+    }
+
+    await certificateProgram.methods
+      .setLamportPrice(lamportPrice)
+      .accounts({ authority: creator.publicKey, certificateDetails: certDetails, assetIdOrMintPk: assetIds.get(certDetails.toBase58()) })
+      .signers([creator])
+      .rpc();
+
+    const treasuryBefore = await connection.getBalance(treasury.publicKey);
+    const creatorBefore = await connection.getBalance(creator.publicKey);
+    const bandMemberBefore = await connection.getBalance(bandMember.publicKey);
+    await program.methods
+      .purchaseLicenceWithSol(lamportPrice)
+      .accounts(solAccounts)
+      .remainingAccounts(beneficiaryWallets)
+      .signers([solBuyer])
+      .rpc();
+
+    // 2.5% platform fee, then the remaining 0.975 SOL split 70/30
+    assert.equal(await connection.getBalance(treasury.publicKey) - treasuryBefore, 25_000_000);
+    assert.equal(await connection.getBalance(creator.publicKey) - creatorBefore, 682_500_000);
+    assert.equal(await connection.getBalance(bandMember.publicKey) - bandMemberBefore, 292_500_000);
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.ok(licenceData.paymentMint.equals(NATIVE_MINT));
+    assert.ok(licenceData.purchasePrice.eq(lamportPrice));
+
+    // A half refund is paid from the revoker's wallet in lamports
+    const holderBefore = await connection.getBalance(solBuyer.publicKey);
+    await program.methods
+      .revokeLicence(5000)
+      .accounts({
+        revoker: creator.publicKey,
+        licence,
+        certificateDetails: certDetails,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+        holder: solBuyer.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([creator])
+      .rpc();
+    assert.equal(await connection.getBalance(solBuyer.publicKey) - holderBefore, web3.LAMPORTS_PER_SOL / 2);
+  });
+
+  // - renew_licence_with_sol: licences paid in lamports renew in lamports
+  it("Renews a SOL licence in lamports", async () => {
+    const certDetails = await registerCertificate(SHORT_TEMPLATE);
+    const lamportPrice = new BN(web3.LAMPORTS_PER_SOL / 10);
+    await certificateProgram.methods
+      .setLamportPrice(lamportPrice)
+      .accounts({ authority: creator.publicKey, certificateDetails: certDetails, assetIdOrMintPk: assetIds.get(certDetails.toBase58()) })
+      .signers([creator])
+      .rpc();
+
+    const solBuyer = anchor.web3.Keypair.generate();
+    await airdrop(solBuyer.publicKey, 1);
+    const licence = licencePda(certDetails, solBuyer.publicKey);
+    const beneficiaryWallets = [
+      { pubkey: creator.publicKey, isWritable: true, isSigner: false },
+      { pubkey: bandMember.publicKey, isWritable: true, isSigner: false },
+    ];
+    await program.methods
+      .purchaseLicenceWithSol(lamportPrice)
+      .accounts({
+        buyer: solBuyer.publicKey,
+        licence,
+        certificateDetails: certDetails,
+        licenceTemplate: templatePda(SHORT_TEMPLATE),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+        treasury: treasury.publicKey,
+      })
+      .remainingAccounts(beneficiaryWallets)
+      .signers([solBuyer])
+      .rpc();
+    const firstExpiry = (await program.account.licence.fetch(licence)).expiryTimestamp.toNumber();
+
+    const treasuryBefore = await connection.getBalance(treasury.publicKey);
+    const creatorBefore = await connection.getBalance(creator.publicKey);
+    const bandMemberBefore = await connection.getBalance(bandMember.publicKey);
+    await program.methods
+      .renewLicenceWithSol(lamportPrice)
+      .accounts({
+        holder: solBuyer.publicKey,
+        licence,
+        certificateDetails: certDetails,
+        licenceTemplate: templatePda(SHORT_TEMPLATE),
+        systemProgram: anchor.web3.SystemProgram.programId,
+        licenceAuthority,
+        certificateProgram: certificateProgram.programId,
+        platformConfig,
+        treasury: treasury.publicKey,
+      })
+      .remainingAccounts(beneficiaryWallets)
+      .signers([solBuyer])
+      .rpc();
+
+    // 2.5% platform fee, then the remaining 0.0975 SOL split 70/30
+    assert.equal(await connection.getBalance(treasury.publicKey) - treasuryBefore, 2_500_000);
+    assert.equal(await connection.getBalance(creator.publicKey) - creatorBefore, 68_250_000);
+    assert.equal(await connection.getBalance(bandMember.publicKey) - bandMemberBefore, 29_250_000);
+    const licenceData = await program.account.licence.fetch(licence);
+    assert.isAtLeast(licenceData.expiryTimestamp.toNumber(), firstExpiry + 2);
+    assert.equal(licenceData.renewalCount, 1);
+  });
+
+  // - purchase_licence: price above the buyer's bound
+  it("Should fail purchase when the total exceeds max_price", async () => {
+    const certDetails = await registerCertificate();
+    const { kp, tokenAccount } = await newBuyer();
+
+    try {
+      await purchase(kp, tokenAccount, certDetails, STANDARD_TEMPLATE, purchasePrice.subn(1));
+      assert.fail("Purchase should have failed above max_price.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "SlippageExceeded");
+    }
+    assert.equal(await balanceOf(tokenAccount), purchasePrice.toNumber(), "Buyer should not have been charged");
+  });
+
+  // - exclusive licences: the lock refuses other buyers until the holder's licence ends
+  it("Holds an exclusive lock until the licence is revoked", async () => {
+    const certDetails = await registerCertificate(EXCLUSIVE_TEMPLATE);
+    const holder = await newBuyer();
+    const licence = await purchase(holder.kp, holder.tokenAccount, certDetails, EXCLUSIVE_TEMPLATE);
+    let certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.ok(certificateData.exclusiveLicence.equals(licence));
+    assert.isNull(certificateData.exclusiveUntil);
+
+    const other = await newBuyer();
+    try {
+      await purchase(other.kp, other.tokenAccount, certDetails, EXCLUSIVE_TEMPLATE);
+      assert.fail("Purchase should have failed while the exclusive lock is held.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "ExclusiveLicenceActive");
+    }
+
+    await program.methods
+      .revokeLicence(0)
+      .accounts({ revoker: creator.publicKey, licence, certificateDetails: certDetails, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+      .signers([creator])
+      .rpc();
+    certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.isNull(certificateData.exclusiveLicence);
+
+    const otherLicence = await purchase(other.kp, other.tokenAccount, certDetails, EXCLUSIVE_TEMPLATE);
+    certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.ok(certificateData.exclusiveLicence.equals(otherLicence));
+  });
+
+  it("Lets only the holder retake a lapsed exclusive lock until its licence is expired", async () => {
+    const certDetails = await registerCertificate(EXCLUSIVE_SHORT_TEMPLATE);
+    const holder = await newBuyer(purchasePrice.mul(new BN(2)));
+    const licence = await purchase(holder.kp, holder.tokenAccount, certDetails, EXCLUSIVE_SHORT_TEMPLATE);
+    await delay(3500);
+
+    // The lock has lapsed, but the holder's licence is still counted until someone expires it
+    const other = await newBuyer();
+    try {
+      await purchase(other.kp, other.tokenAccount, certDetails, EXCLUSIVE_SHORT_TEMPLATE);
+      assert.fail("Purchase should have failed while the lapsed holder is still counted.");
+    } catch (err) {
+      assert.equal(err.error.errorCode.code, "LicencesOutstanding");
+    }
+
+    // The holder renews and takes the lock back
+    await renew(holder.kp, holder.tokenAccount, licence, certDetails, EXCLUSIVE_SHORT_TEMPLATE);
+    let certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.ok(certificateData.exclusiveLicence.equals(licence));
+    const renewed = await program.account.licence.fetch(licence);
+    assert.equal(certificateData.exclusiveUntil.toNumber(), renewed.expiryTimestamp.toNumber());
+
+    // Expiring the licence releases the lock for the next buyer
+    await delay(3500);
+    await program.methods
+      .expireLicence(false)
+      .accounts({ licence, buyer: holder.kp.publicKey, certificateDetails: certDetails, licenceAuthority, certificateProgram: certificateProgram.programId, platformConfig })
+      .rpc();
+    certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.isNull(certificateData.exclusiveLicence);
+    assert.equal(certificateData.activeLicences, 0);
+
+    const otherLicence = await purchase(other.kp, other.tokenAccount, certDetails, EXCLUSIVE_SHORT_TEMPLATE);
+    certificateData = await certificateProgram.account.certificateDetails.fetch(certDetails);
+    assert.ok(certificateData.exclusiveLicence.equals(otherLicence));
+  });
+
+  describe("payment mints", () => {
+    // A payment mint under `tokenProgram` with its own Treasury PDA and royalty accounts,
+    // accepted on the platform alongside `paymentMint`
+    const setUpPaymentMint = async (mint: web3.PublicKey, tokenProgram: web3.PublicKey) => {
+      configParams = { ...configParams, acceptedPaymentMints: [...configParams.acceptedPaymentMints, mint] };
+      await certificateProgram.methods
+        .updateConfig(configParams)
+        .accounts({ admin: buyer.publicKey, platformConfig })
+        .rpc();
+
+      const [treasuryAccount] = anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("treasury"), mint.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .initializeTreasury()
+        .accounts({
+          admin: buyer.publicKey,
+          platformConfig,
+          paymentMint: mint,
+          treasuryTokenAccount: treasuryAccount,
+          tokenProgram,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .rpc();
+
+      const tokenAccountFor = (owner: web3.PublicKey) =>
+        createAccount(connection, buyer.payer, mint, owner, anchor.web3.Keypair.generate(), undefined, tokenProgram);
+      return {
+        mint,
+        tokenProgram,
+        treasuryTokenAccount: treasuryAccount,
+        creatorTokenAccount: await tokenAccountFor(creator.publicKey),
+        bandMemberTokenAccount: await tokenAccountFor(bandMember.publicKey),
+        balanceOf: async (tokenAccount: web3.PublicKey): Promise<number> =>
+          Number((await getAccount(connection, tokenAccount, undefined, tokenProgram)).amount),
+        // Funds a fresh buyer with SOL for fees and `tokens` of this mint
+        newBuyer: async (tokens: BN) => {
+          const kp = anchor.web3.Keypair.generate();
+          await airdrop(kp.publicKey, 0.5);
+          const tokenAccount = await tokenAccountFor(kp.publicKey);
+          await mintTo(connection, buyer.payer, mint, tokenAccount, buyer.publicKey, tokens.toNumber(), [], undefined, tokenProgram);
+          return { kp, tokenAccount };
+        },
+      };
+    };
+    type PaymentMint = Awaited<ReturnType<typeof setUpPaymentMint>>;
+
+    // Buys a licence through purchase_licence, paying in `payment`'s mint. Returns the Licence PDA.
+    const purchaseIn = async (
+      payment: PaymentMint,
+      buyerKP: web3.Keypair,
+      buyerTokenAccount: web3.PublicKey,
+      certificateDetails: web3.PublicKey,
+      maxPrice: BN
+    ): Promise<web3.PublicKey> => {
+      const licence = licencePda(certificateDetails, buyerKP.publicKey);
+      await program.methods
+        .purchaseLicence(maxPrice)
+        .accounts({
+          buyer: buyerKP.publicKey,
+          buyerTokenAccount,
+          licence,
+          certificateDetails,
+          licenceTemplate: templatePda(STANDARD_TEMPLATE),
+          paymentMint: payment.mint,
+          tokenProgram: payment.tokenProgram,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          licenceAuthority,
+          certificateProgram: certificateProgram.programId,
+          platformConfig,
+          treasuryTokenAccount: payment.treasuryTokenAccount,
+        })
+        .remainingAccounts([
+          { pubkey: payment.creatorTokenAccount, isWritable: true, isSigner: false },
+          { pubkey: payment.bandMemberTokenAccount, isWritable: true, isSigner: false },
+        ])
+        .signers([buyerKP])
+        .rpc();
+      return licence;
+    };
+
+    after(async () => {
+      configParams = { ...configParams, acceptedPaymentMints: [paymentMint] };
+      await certificateProgram.methods
+        .updateConfig(configParams)
+        .accounts({ admin: buyer.publicKey, platformConfig })
+        .rpc();
+    });
+
+    // purchase_licence takes either token program through the same accounts
+    for (const [label, tokenProgram] of [["an SPL Token", TOKEN_PROGRAM_ID], ["a Token-2022", TOKEN_2022_PROGRAM_ID]] as const) {
+      it(`Purchases a licence paid in ${label} mint`, async () => {
+        const mint = await createMint(connection, buyer.payer, buyer.publicKey, null, 6, anchor.web3.Keypair.generate(), undefined, tokenProgram);
+        const payment = await setUpPaymentMint(mint, tokenProgram);
+        const certDetails = await registerCertificate(STANDARD_TEMPLATE, mint);
+        const { kp, tokenAccount } = await payment.newBuyer(purchasePrice);
+
+        const licence = await purchaseIn(payment, kp, tokenAccount, certDetails, purchasePrice);
+
+        // 2.5% platform fee, then the remaining 97.5 tokens split 70/30
+        assert.equal(await payment.balanceOf(tokenAccount), 0);
+        assert.equal(await payment.balanceOf(payment.treasuryTokenAccount), 2_500_000);
+        assert.equal(await payment.balanceOf(payment.creatorTokenAccount), 68_250_000);
+        assert.equal(await payment.balanceOf(payment.bandMemberTokenAccount), 29_250_000);
+        const licenceData = await program.account.licence.fetch(licence);
+        assert.ok(licenceData.paymentMint.equals(mint));
+        assert.ok(licenceData.purchasePrice.eq(purchasePrice));
+        assert.ok(licenceData.transferFeePaid.isZero());
+      });
+    }
+
+    it("Grosses up every leg of a purchase paid in a transfer-fee mint", async () => {
+      // Token-2022 mint charging 1% on every transfer, with no practical cap
+      const mintKP = anchor.web3.Keypair.generate();
+      const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+      await provider.sendAndConfirm(
+        new anchor.web3.Transaction().add(
+          anchor.web3.SystemProgram.createAccount({
+            fromPubkey: buyer.publicKey,
+            newAccountPubkey: mintKP.publicKey,
+            space: mintLen,
+            lamports: await connection.getMinimumBalanceForRentExemption(mintLen),
+            programId: TOKEN_2022_PROGRAM_ID,
+          }),
+          createInitializeTransferFeeConfigInstruction(
+            mintKP.publicKey, buyer.publicKey, buyer.publicKey, 100, BigInt(10**12), TOKEN_2022_PROGRAM_ID
+          ),
+          createInitializeMintInstruction(mintKP.publicKey, 6, buyer.publicKey, null, TOKEN_2022_PROGRAM_ID)
+        ),
+        [mintKP]
+      );
+      const payment = await setUpPaymentMint(mintKP.publicKey, TOKEN_2022_PROGRAM_ID);
+      const certDetails = await registerCertificate(STANDARD_TEMPLATE, mintKP.publicKey);
+      const { kp, tokenAccount } = await payment.newBuyer(purchasePrice.mul(new BN(2)));
+
+      // Each leg is sent as ceil(net * 10000 / 9900) so that 1% off it leaves the net share
+      const fees = { treasury: 25_253, creator: 689_394, bandMember: 295_455 };
+      const transferFees = fees.treasury + fees.creator + fees.bandMember;
+
+      // A max_price of the bare listing price does not cover the transfer fees
+      try {
+        await purchaseIn(payment, kp, tokenAccount, certDetails, purchasePrice);
+        assert.fail("Purchase should have failed with max_price below the grossed-up total.");
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "SlippageExceeded");
+      }
+
+      const licence = await purchaseIn(payment, kp, tokenAccount, certDetails, purchasePrice.add(new BN(transferFees)));
+
+      // The buyer pays the price plus every leg's fee; each recipient nets its full share
+      assert.equal(await payment.balanceOf(tokenAccount), purchasePrice.toNumber() - transferFees); // Funded with twice the price
+      assert.equal(await payment.balanceOf(payment.treasuryTokenAccount), 2_500_000);
+      assert.equal(await payment.balanceOf(payment.creatorTokenAccount), 68_250_000);
+      assert.equal(await payment.balanceOf(payment.bandMemberTokenAccount), 29_250_000);
+      const licenceData = await program.account.licence.fetch(licence);
+      assert.ok(licenceData.purchasePrice.eq(purchasePrice));
+      assert.equal(licenceData.transferFeePaid.toNumber(), transferFees);
+    });
+  });
+
+  describe("escrowed purchases", () => {
+    // Two-second dispute window for the escrow tests, restored afterwards
+    before(async () => {
+      await setEscrowPeriod(2);
+    });
+    after(async () => {
+      await setEscrowPeriod(0);
+    });
+
+    it("Holds an escrowed purchase until the dispute window closes", async () => {
+      const certDetails = await registerCertificate();
+      const { kp, tokenAccount } = await newBuyer();
+      const licence = await purchaseEscrowed(kp, tokenAccount, certDetails);
+
+      assert.equal(await balanceOf(escrowPda(licence)), purchasePrice.toNumber());
+      assert.isNotNull((await program.account.licence.fetch(licence)).escrowReleaseAt);
+      assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 1);
+
+      // Direct purchases are refused while the platform requires escrow
+      const other = await newBuyer();
+      try {
+        await purchase(other.kp, other.tokenAccount, certDetails);
+        assert.fail("Direct purchase should have failed while escrow is required.");
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "EscrowRequired");
+      }
+
+      try {
+        await releaseEscrow(licence, kp.publicKey, certDetails);
+        assert.fail("Release should have failed inside the dispute window.");
+      } catch (err) {
+        assert.equal(err.error.errorCode.code, "EscrowLocked");
+      }
+
+      await delay(3000);
+      const treasuryBefore = await balanceOf(treasuryTokenAccount);
+      const creatorBefore = await balanceOf(creatorTokenAccount);
+      const bandMemberBefore = await balanceOf(bandMemberTokenAccount);
+      await releaseEscrow(licence, kp.publicKey, certDetails);
+
+      assert.equal(await balanceOf(treasuryTokenAccount) - treasuryBefore, 2_500_000);
+      assert.equal(await balanceOf(creatorTokenAccount) - creatorBefore, 68_250_000);
+      assert.equal(await balanceOf(bandMemberTokenAccount) - bandMemberBefore, 29_250_000);
+      assert.isNull(await connection.getAccountInfo(escrowPda(licence)), "Escrow account should be closed");
+      assert.isNull((await program.account.licence.fetch(licence)).escrowReleaseAt);
+    });
+
+    it("Refunds an escrowed purchase revoked inside the dispute window", async () => {
+      const certDetails = await registerCertificate();
+      const { kp, tokenAccount } = await newBuyer();
+      const licence = await purchaseEscrowed(kp, tokenAccount, certDetails);
+      assert.equal(await balanceOf(tokenAccount), 0);
+
+      await program.methods
+        .revokeLicence(0)
+        .accounts({
+          revoker: creator.publicKey,
+          licence,
+          certificateDetails: certDetails,
+          licenceAuthority,
+          certificateProgram: certificateProgram.programId,
+          platformConfig,
+          escrowTokenAccount: escrowPda(licence),
+          buyerTokenAccount: tokenAccount,
+          buyer: kp.publicKey,
+          paymentMint,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([creator])
+        .rpc();
+
+      assert.equal(await balanceOf(tokenAccount), purchasePrice.toNumber());
+      const licenceData = await program.account.licence.fetch(licence);
+      assert.equal(JSON.stringify(licenceData.status), JSON.stringify({ revoked: {} }));
+      assert.isNull(licenceData.escrowReleaseAt);
+      assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 0);
+    });
+
+    it("Keeps a licence revoked after the window counted until its escrow is released", async () => {
+      const certDetails = await registerCertificate();
+      const { kp, tokenAccount } = await newBuyer();
+      const licence = await purchaseEscrowed(kp, tokenAccount, certDetails);
+      await delay(3000);
+
+      // Past the window there is nothing to refund, but the escrow still holds the price
+      await program.methods
+        .revokeLicence(0)
+        .accounts({
+          revoker: creator.publicKey,
+          licence,
+          certificateDetails: certDetails,
+          licenceAuthority,
+          certificateProgram: certificateProgram.programId,
+          platformConfig,
+        })
+        .signers([creator])
+        .rpc();
+      assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 1);
+
+      // Retiring cannot close the certificate while the escrow is pending
+      await retireCertificate(certDetails);
+      const retired = await certificateProgram.account.certificateDetails.fetch(certDetails);
+      assert.equal(JSON.stringify(retired.status), JSON.stringify({ retired: {} }));
+
+      await releaseEscrow(licence, kp.publicKey, certDetails);
+      assert.equal((await certificateProgram.account.certificateDetails.fetch(certDetails)).activeLicences, 0);
+
+      // Now the certificate can be closed
+      await retireCertificate(certDetails);
+      assert.isNull(await certificateProgram.account.certificateDetails.fetchNullable(certDetails));
+    });
+  });
 
-});
\ No newline at end of file
+});